@@ -1,14 +1,17 @@
 use pathtracer::shape::*;
 use pathtracer::Camera;
 use pathtracer::Color;
+use pathtracer::Error;
 use pathtracer::Hitable;
 use pathtracer::Material;
+use pathtracer::RenderSettings;
+use pathtracer::SamplerKind;
 use pathtracer::Scene;
 use pathtracer::Texture;
 use pathtracer::Vec3;
 use pathtracer::BVH;
 
-fn cornell_box(aspect_ratio: f64) -> Scene {
+fn cornell_box(aspect_ratio: f64) -> Result<Scene, Error> {
     let red = Color::new(0.75, 0.25, 0.25);
     let white = Color::new(0.75, 0.75, 0.75);
     let blue = Color::new(0.25, 0.25, 0.75);
@@ -75,31 +78,25 @@ fn cornell_box(aspect_ratio: f64) -> Scene {
     let look_from = Vec3::new(0.0, 5.0, 15.0);
     let look_at = Vec3::new(0.0, 5.0, 0.0);
 
-    Scene {
-        camera: Camera::new(look_from, look_at, 45.0, aspect_ratio, 0.0),
-        world: BVH::from_vec(objects),
-    }
+    Ok(Scene {
+        camera: Camera::new(look_from, look_at, 45.0, aspect_ratio, 0.0)?,
+        world: BVH::from_vec(objects)?,
+    })
 }
 
-fn main() {
-    let width = 640;
-    let height = 480;
-    let samples = 2500;
-    let aspect_ratio = f64::from(width) / f64::from(height);
-    let gamma = 2.2f64;
-    let max_depth = 100;
-    let workers: usize = 12;
+fn main() -> Result<(), Error> {
+    let settings = RenderSettings {
+        width: 640,
+        height: 480,
+        samples: 10000,
+        max_depth: 100,
+        gamma: 2.2,
+        workers: 12,
+        sampler: SamplerKind::Sobol,
+        ..RenderSettings::default()
+    };
 
-    let scene = cornell_box(aspect_ratio);
+    let scene = cornell_box(settings.aspect_ratio())?;
 
-    pathtracer::render(
-        scene,
-        width,
-        height,
-        samples,
-        max_depth,
-        gamma,
-        workers,
-        "output/cornell.png",
-    );
+    pathtracer::render(scene, &settings, "output/cornell.png")
 }
@@ -0,0 +1,35 @@
+use pathtracer::Color;
+use pathtracer::Denoise;
+
+use std::error::Error;
+use std::process;
+
+/// Denoises a render from its beauty, albedo and normal AOVs.
+///
+/// Usage: denoise <image> <beauty.exr> <albedo.exr> <normal.exr>
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.len() != 4 {
+        eprintln!("Usage: denoise <image> <beauty.exr> <albedo.exr> <normal.exr>");
+        process::exit(1);
+    }
+
+    let gamma = 2.2f64;
+
+    let beauty = image::open(&args[1])?.to_rgb32f();
+    let albedo = image::open(&args[2])?.to_rgb32f();
+    let normal = image::open(&args[3])?.to_rgb32f();
+
+    let denoised = pathtracer::denoise(&beauty, &albedo, &normal, &Denoise::default())?;
+
+    let image = image::RgbImage::from_fn(denoised.width(), denoised.height(), |x, y| {
+        let [r, g, b] = denoised.get_pixel(x, y).0;
+
+        Color::new(f64::from(r), f64::from(g), f64::from(b)).to_gamma_rgb(gamma.recip())
+    });
+
+    image.save(&args[0])?;
+
+    Ok(())
+}
@@ -1,14 +1,20 @@
 use pathtracer::shape::*;
+use pathtracer::AdaptiveSampling;
 use pathtracer::Camera;
 use pathtracer::Color;
+use pathtracer::Error;
 use pathtracer::Hitable;
 use pathtracer::Material;
+use pathtracer::RenderSettings;
 use pathtracer::Scene;
 use pathtracer::Texture;
+use pathtracer::TextureCache;
 use pathtracer::Vec3;
 use pathtracer::BVH;
 
-fn earth_moon(aspect_ratio: f64) -> Scene {
+fn earth_moon(aspect_ratio: f64) -> Result<Scene, Error> {
+    let mut textures = TextureCache::new();
+
     let objects: Vec<Hitable> = vec![
         Box::new(Disc {
             center: Vec3::new(1000.0, 0.0, 0.0),
@@ -21,43 +27,41 @@ fn earth_moon(aspect_ratio: f64) -> Scene {
         Box::new(Sphere {
             center: Vec3::new(-9.0, 0.0, 0.0),
             radius: 10.0,
-            material: Material::lambertian(Texture::bitmap("examples/textures/earth.jpg")),
+            material: Material::lambertian(textures.bitmap("examples/textures/earth.jpg")?),
         }),
         Box::new(Sphere {
             center: Vec3::new(13.0, 0.0, 0.0),
             radius: 5.0,
-            material: Material::lambertian(Texture::bitmap("examples/textures/moon.jpg")),
+            material: Material::lambertian(textures.bitmap("examples/textures/moon.jpg")?),
         }),
     ];
 
     let look_from = Vec3::new(0.0, 20.0, 30.0);
     let look_at = Vec3::new(0.0, 0.0, 0.0);
 
-    Scene {
-        camera: Camera::new(look_from, look_at, 45.0, aspect_ratio, 0.0),
-        world: BVH::from_vec(objects),
-    }
+    Ok(Scene {
+        camera: Camera::new(look_from, look_at, 45.0, aspect_ratio, 0.0)?,
+        world: BVH::from_vec(objects)?,
+    })
 }
 
-fn main() {
-    let width = 640;
-    let height = 480;
-    let samples = 5000;
-    let aspect_ratio = f64::from(width) / f64::from(height);
-    let gamma = 2.2f64;
-    let max_depth = 50;
-    let workers: usize = 8;
+fn main() -> Result<(), Error> {
+    let settings = RenderSettings {
+        width: 640,
+        height: 480,
+        samples: 20000,
+        max_depth: 50,
+        gamma: 2.2,
+        workers: 8,
+        adaptive: Some(AdaptiveSampling {
+            min_samples: 256,
+            threshold: 0.005,
+            ..AdaptiveSampling::default()
+        }),
+        ..RenderSettings::default()
+    };
 
-    let scene = earth_moon(aspect_ratio);
+    let scene = earth_moon(settings.aspect_ratio())?;
 
-    pathtracer::render(
-        scene,
-        width,
-        height,
-        samples,
-        max_depth,
-        gamma,
-        workers,
-        "output/earth-moon.png",
-    );
+    pathtracer::render(scene, &settings, "output/earth-moon.png")
 }
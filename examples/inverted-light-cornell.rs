@@ -1,14 +1,16 @@
 use pathtracer::shape::*;
 use pathtracer::Camera;
 use pathtracer::Color;
+use pathtracer::Error;
 use pathtracer::Hitable;
 use pathtracer::Material;
+use pathtracer::RenderSettings;
 use pathtracer::Scene;
 use pathtracer::Texture;
 use pathtracer::Vec3;
 use pathtracer::BVH;
 
-fn cornell_box(aspect_ratio: f64) -> Scene {
+fn cornell_box(aspect_ratio: f64) -> Result<Scene, Error> {
     let red = Color::new(0.75, 0.25, 0.25);
     let white = Color::new(0.75, 0.75, 0.75);
     let blue = Color::new(0.25, 0.25, 0.75);
@@ -86,31 +88,24 @@ fn cornell_box(aspect_ratio: f64) -> Scene {
     let look_from = Vec3::new(0.0, 9.95, 8.0);
     let look_at = Vec3::new(0.0, 3.0, -5.0);
 
-    Scene {
-        camera: Camera::new(look_from, look_at, 55.0, aspect_ratio, 0.0),
-        world: BVH::from_vec(objects),
-    }
+    Ok(Scene {
+        camera: Camera::new(look_from, look_at, 55.0, aspect_ratio, 0.0)?,
+        world: BVH::from_vec(objects)?,
+    })
 }
 
-fn main() {
-    let width = 1280;
-    let height = 720;
-    let samples = 10000;
-    let aspect_ratio = f64::from(width) / f64::from(height);
-    let gamma = 2.2f64;
-    let max_depth = 50;
-    let workers: usize = 12;
+fn main() -> Result<(), Error> {
+    let settings = RenderSettings {
+        width: 1280,
+        height: 720,
+        samples: 40000,
+        max_depth: 50,
+        gamma: 2.2,
+        workers: 12,
+        ..RenderSettings::default()
+    };
 
-    let scene = cornell_box(aspect_ratio);
+    let scene = cornell_box(settings.aspect_ratio())?;
 
-    pathtracer::render(
-        scene,
-        width,
-        height,
-        samples,
-        max_depth,
-        gamma,
-        workers,
-        "output/inverted-light-cornell.png",
-    );
+    pathtracer::render(scene, &settings, "output/inverted-light-cornell.png")
 }
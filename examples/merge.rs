@@ -0,0 +1,19 @@
+use pathtracer::Error;
+
+use std::process;
+
+/// Combines partial renders into an image.
+///
+/// Usage: merge <image> <partial>...
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.len() < 2 {
+        eprintln!("Usage: merge <image> <partial>...");
+        process::exit(1);
+    }
+
+    let gamma = 2.2f64;
+
+    pathtracer::merge(&args[1..], gamma, &args[0])
+}
@@ -1,14 +1,17 @@
 use pathtracer::shape::*;
 use pathtracer::Camera;
 use pathtracer::Color;
+use pathtracer::DiffuseLight;
+use pathtracer::Error;
 use pathtracer::Hitable;
 use pathtracer::Material;
+use pathtracer::RenderSettings;
 use pathtracer::Scene;
 use pathtracer::Texture;
 use pathtracer::Vec3;
 use pathtracer::BVH;
 
-fn raytracing_one_weekend(aspect_ratio: f64) -> Scene {
+fn raytracing_one_weekend(aspect_ratio: f64) -> Result<Scene, Error> {
     let mut list: Vec<Hitable> = Vec::new();
     let radius = 0.2f64;
 
@@ -85,37 +88,35 @@ fn raytracing_one_weekend(aspect_ratio: f64) -> Scene {
     list.push(Box::new(Sphere {
         center: Vec3::new(0.0, 0.0, 0.0),
         radius: 5000.0,
-        material: Material::diffuse_light(Texture::constant_color(Color::new(0.5, 0.7, 1.0))),
+        // Seen from the inside.
+        material: Material::light(DiffuseLight {
+            texture: Texture::constant_color(Color::new(0.5, 0.7, 1.0)),
+            two_sided: true,
+            ..DiffuseLight::default()
+        }),
     }));
 
     let look_from = Vec3::new(13.0, 2.0, 3.0);
     let look_at = Vec3::new(0.0, 0.0, 0.0);
 
-    Scene {
-        camera: Camera::new(look_from, look_at, 20.0, aspect_ratio, 0.0),
-        world: BVH::from_vec(list),
-    }
+    Ok(Scene {
+        camera: Camera::new(look_from, look_at, 20.0, aspect_ratio, 0.0)?,
+        world: BVH::from_vec(list)?,
+    })
 }
 
-fn main() {
-    let width = 640;
-    let height = 480;
-    let samples = 1000;
-    let aspect_ratio = f64::from(width) / f64::from(height);
-    let gamma = 2.2f64;
-    let max_depth = 10;
-    let workers: usize = 12;
+fn main() -> Result<(), Error> {
+    let settings = RenderSettings {
+        width: 640,
+        height: 480,
+        samples: 4000,
+        max_depth: 10,
+        gamma: 2.2,
+        workers: 12,
+        ..RenderSettings::default()
+    };
 
-    let scene = raytracing_one_weekend(aspect_ratio);
+    let scene = raytracing_one_weekend(settings.aspect_ratio())?;
 
-    pathtracer::render(
-        scene,
-        width,
-        height,
-        samples,
-        max_depth,
-        gamma,
-        workers,
-        "output/one-weekend.png",
-    );
+    pathtracer::render(scene, &settings, "output/one-weekend.png")
 }
@@ -3,6 +3,8 @@
 use crate::ray::Ray;
 use crate::Vec3;
 
+/// Box whose faces are aligned with the axes, spanning from `min` to `max`.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug)]
 pub struct AABB {
     pub min: Vec3,
@@ -25,6 +27,10 @@ impl AABB {
         AABB { min, max }
     }
 
+    pub fn is_finite(&self) -> bool {
+        self.min.is_finite() && self.max.is_finite()
+    }
+
     pub fn intersect(&self, ray: &Ray, tmin: f64, tmax: f64) -> bool {
         // Check X axis
         let mut inv_d = ray.direction.x.recip();
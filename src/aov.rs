@@ -0,0 +1,169 @@
+//! Arbitrary output variables, images of properties of the scene saved along
+//! with the rendered image for compositing.
+
+use crate::hash;
+use crate::material::Lobe;
+use crate::Color;
+use crate::Vec3;
+
+/// Arbitrary output variable (AOV).
+///
+/// Every AOV is filtered like the rendered image, so edges blend the values
+/// of the surfaces on each side. Pixels where the camera rays hit nothing get
+/// zero in the properties of the first hit.
+///
+/// * `Depth` - Distance from the camera to the first hit.
+/// * `Normal` - Normal of the first hit in world space.
+/// * `Albedo` - Color of the surface at the first hit.
+/// * `Position` - Position of the first hit in world space.
+/// * `Uv` - Texture coordinates of the first hit, in the red and green
+///   channels.
+/// * `ObjectId` - A different color for every object, see `Intersection`.
+/// * `MaterialId` - A different color for every material, objects with equal
+///   materials share it, see `Intersection::material_id`.
+/// * `Emission` - Light emitted by the first hit towards the camera.
+/// * `DiffuseDirect` - Light coming from an emitter straight to a diffuse
+///   first hit.
+/// * `DiffuseIndirect` - Light reaching a diffuse first hit after bouncing
+///   around the scene.
+/// * `SpecularDirect` - Light coming from an emitter straight to a specular
+///   (metal or glass) first hit.
+/// * `SpecularIndirect` - Light reaching a specular first hit after bouncing
+///   around the scene.
+/// * `Beauty` - The rendered image before gamma correction, the sum of the
+///   five above.
+///
+/// With `Beauty`, `Albedo` and `Normal` a render can be denoised later on,
+/// see `denoise`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aov {
+    Depth,
+    Normal,
+    Albedo,
+    Position,
+    Uv,
+    ObjectId,
+    MaterialId,
+    Emission,
+    DiffuseDirect,
+    DiffuseIndirect,
+    SpecularDirect,
+    SpecularIndirect,
+    Beauty,
+}
+
+impl Aov {
+    /// Every AOV, in declaration order so an AOV can be looked up by its
+    /// discriminant.
+    pub const ALL: [Aov; 13] = [
+        Aov::Depth,
+        Aov::Normal,
+        Aov::Albedo,
+        Aov::Position,
+        Aov::Uv,
+        Aov::ObjectId,
+        Aov::MaterialId,
+        Aov::Emission,
+        Aov::DiffuseDirect,
+        Aov::DiffuseIndirect,
+        Aov::SpecularDirect,
+        Aov::SpecularIndirect,
+        Aov::Beauty,
+    ];
+
+    /// Name of the AOV, used in the filename it's saved with.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::Position => "position",
+            Aov::Uv => "uv",
+            Aov::ObjectId => "object_id",
+            Aov::MaterialId => "material_id",
+            Aov::Emission => "emission",
+            Aov::DiffuseDirect => "diffuse_direct",
+            Aov::DiffuseIndirect => "diffuse_indirect",
+            Aov::SpecularDirect => "specular_direct",
+            Aov::SpecularIndirect => "specular_indirect",
+            Aov::Beauty => "beauty",
+        }
+    }
+}
+
+/// Properties of the first surface a camera ray hit.
+#[derive(Clone, Debug)]
+pub struct FirstHit {
+    pub distance: f64,
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub u: f64,
+    pub v: f64,
+    pub albedo: Color,
+    pub object_id: u32,
+    pub material_id: u64,
+}
+
+/// What a camera ray hit first and the light it brought back, split by how
+/// it got there.
+#[derive(Clone, Debug, Default)]
+pub struct PathSample {
+    pub hit: Option<FirstHit>,
+    pub emission: Color,
+    pub diffuse_direct: Color,
+    pub diffuse_indirect: Color,
+    pub specular_direct: Color,
+    pub specular_indirect: Color,
+}
+
+impl PathSample {
+    /// Adds light emitted at the `depth`th hit of the path, `lobe` being how
+    /// the first hit scattered the ray.
+    pub fn add_light(&mut self, light: Color, depth: u32, lobe: Option<Lobe>) {
+        match (lobe, depth) {
+            (None, _) => self.emission += light,
+            (Some(Lobe::Diffuse), 2) => self.diffuse_direct += light,
+            (Some(Lobe::Diffuse), _) => self.diffuse_indirect += light,
+            (Some(Lobe::Specular), 2) => self.specular_direct += light,
+            (Some(Lobe::Specular), _) => self.specular_indirect += light,
+        }
+    }
+
+    /// All the light the ray brought back.
+    pub fn radiance(&self) -> Color {
+        self.emission
+            + self.diffuse_direct
+            + self.diffuse_indirect
+            + self.specular_direct
+            + self.specular_indirect
+    }
+
+    pub fn aov(&self, aov: Aov) -> Color {
+        let vector = |v: Vec3| Color::new(v.x, v.y, v.z);
+
+        match (aov, &self.hit) {
+            (Aov::Emission, _) => self.emission,
+            (Aov::DiffuseDirect, _) => self.diffuse_direct,
+            (Aov::DiffuseIndirect, _) => self.diffuse_indirect,
+            (Aov::SpecularDirect, _) => self.specular_direct,
+            (Aov::SpecularIndirect, _) => self.specular_indirect,
+            (Aov::Beauty, _) => self.radiance(),
+            (_, None) => Color::default(),
+            (Aov::Depth, Some(hit)) => Color::new(hit.distance, hit.distance, hit.distance),
+            (Aov::Normal, Some(hit)) => vector(hit.normal),
+            (Aov::Albedo, Some(hit)) => hit.albedo,
+            (Aov::Position, Some(hit)) => vector(hit.position),
+            (Aov::Uv, Some(hit)) => Color::new(hit.u, hit.v, 0.0),
+            (Aov::ObjectId, Some(hit)) => id_color(u64::from(hit.object_id)),
+            (Aov::MaterialId, Some(hit)) => id_color(hit.material_id),
+        }
+    }
+}
+
+/// Color picked by scrambling the bits of `id`, so different ids, even
+/// consecutive ones, get different colors.
+pub fn id_color(id: u64) -> Color {
+    let hash = hash::finalize(id);
+
+    Color::from_u8(hash as u8, (hash >> 8) as u8, (hash >> 16) as u8)
+}
@@ -1,36 +1,188 @@
 //! Bounding volume hierarchy.
 
 use crate::aabb::AABB;
+use crate::hash;
 use crate::intersectable::{Intersectable, Intersection};
+use crate::material::Scatterable;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::Error;
 use crate::Hitable;
+use crate::Material;
+use crate::Vec3;
 
-use rand::Rng;
+use std::cell::Cell;
+use std::sync::Arc;
+
+thread_local! {
+    static INTERSECTION_TESTS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Bounding box and primitive intersection tests done so far by the BVHs on
+/// this thread. Used to tell how expensive a ray was.
+pub fn intersection_tests() -> u64 {
+    INTERSECTION_TESTS.with(Cell::get)
+}
+
+fn count_intersection_test() {
+    INTERSECTION_TESTS.with(|tests| tests.set(tests.get() + 1));
+}
 
 #[derive(Debug)]
 pub struct BVH {
     left: Option<Hitable>,
     right: Option<Hitable>,
     bounding_box: AABB,
+    fingerprint: u64,
+    lights: Vec<Indexed>,
+}
+
+/// Point picked on a light by `BVH::sample_light`.
+pub(crate) struct LightSample {
+    pub object_id: u32,
+    pub point: Vec3,
 }
 
 impl BVH {
-    pub fn from_vec(mut objects: Vec<Hitable>) -> Self {
+    /// Builds the hierarchy for the given objects.
+    ///
+    /// Fails if there are no objects or if any of them has a bounding box
+    /// with NaN or infinite coordinates.
+    pub fn from_vec(objects: Vec<Hitable>) -> Result<Self, Error> {
         if objects.is_empty() {
-            panic!("I need a non-empty object list!");
+            return Err(Error::EmptyScene);
+        }
+
+        if let Some(index) = objects
+            .iter()
+            .position(|object| !object.bounding_box().is_finite())
+        {
+            return Err(Error::InvalidBoundingBox { index });
         }
 
-        let mut rng = rand::thread_rng();
-        let axis: usize = rng.gen_range(0..3);
+        // Hash the objects in the order given, the tree itself is shaped by
+        // how objects are sorted while building it.
+        let fingerprint = objects.iter().fold(objects.len() as u64, |acc, object| {
+            acc.rotate_left(5) ^ hash::debug_hash(object)
+        });
+
+        let objects: Vec<Indexed> = objects
+            .into_iter()
+            .enumerate()
+            .map(|(index, object)| Indexed {
+                index: index as u32,
+                material_id: object.material().map(hash::debug_hash),
+                object: Arc::from(object),
+            })
+            .collect();
+
+        let lights = objects
+            .iter()
+            .filter(|indexed| {
+                let object = &indexed.object;
+
+                object.material().is_some_and(|m| m.is_emissive()) && object.area() > 0.0
+            })
+            .cloned()
+            .collect();
+
+        let objects = objects
+            .into_iter()
+            .map(|indexed| -> Hitable { Box::new(indexed) })
+            .collect();
+
+        let mut bvh = Self::build(objects);
+        bvh.fingerprint = fingerprint;
+        bvh.lights = lights;
+
+        Ok(bvh)
+    }
+
+    /// Whether there are emissive objects that can be sampled.
+    pub(crate) fn has_lights(&self) -> bool {
+        !self.lights.is_empty()
+    }
+
+    /// Picks a light and a point on it, every light being as likely.
+    pub(crate) fn sample_light(&self, sampler: &mut Sampler) -> Option<LightSample> {
+        let count = self.lights.len();
+
+        if count == 0 {
+            return None;
+        }
+
+        let index = ((sampler.get_1d() * count as f64) as usize).min(count - 1);
+        let light = &self.lights[index];
+
+        light
+            .object
+            .sample_surface(sampler)
+            .map(|point| LightSample {
+                object_id: light.index,
+                point,
+            })
+    }
+
+    /// Probability density, over solid angle as seen from `origin`, of
+    /// `sample_light` picking the point of `intersection`. Zero if it isn't
+    /// on a light.
+    pub(crate) fn light_pdf(&self, origin: Vec3, intersection: &Intersection) -> f64 {
+        let light = match self
+            .lights
+            .binary_search_by_key(&intersection.object_id, |light| light.index)
+        {
+            Ok(index) => &self.lights[index],
+            Err(_) => return 0.0,
+        };
+
+        let to_light = intersection.p - origin;
+        let distance_squared = to_light.norm();
+        let cosine = intersection
+            .normal
+            .normalize()
+            .dot(to_light / distance_squared.sqrt())
+            .abs();
+
+        if cosine <= 0.0 {
+            return 0.0;
+        }
+
+        distance_squared / (cosine * light.object.area() * self.lights.len() as f64)
+    }
+
+    /// Hash identifying the objects of the hierarchy.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    fn build(mut objects: Vec<Hitable>) -> Self {
+        // Split along the axis where the objects are most spread out.
+        let bounds = objects
+            .iter()
+            .map(|object| object.bounding_box())
+            .reduce(AABB::surrounding)
+            .unwrap_or(AABB {
+                min: Vec3::zero(),
+                max: Vec3::zero(),
+            });
+        let extent = bounds.max - bounds.min;
+
+        let axis = if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        };
 
         objects.sort_by(|a, b| {
             let a_box = a.bounding_box();
             let b_box = b.bounding_box();
 
             match axis {
-                0 => a_box.min.x.partial_cmp(&b_box.min.x).unwrap(),
-                1 => a_box.min.y.partial_cmp(&b_box.min.y).unwrap(),
-                _ => a_box.min.z.partial_cmp(&b_box.min.z).unwrap(),
+                0 => a_box.min.x.total_cmp(&b_box.min.x),
+                1 => a_box.min.y.total_cmp(&b_box.min.y),
+                _ => a_box.min.z.total_cmp(&b_box.min.z),
             }
         });
 
@@ -52,8 +204,8 @@ impl BVH {
                 size => {
                     let rest = objects.split_off(size / 2);
 
-                    let l = Self::from_vec(objects);
-                    let r = Self::from_vec(rest);
+                    let l = Self::build(objects);
+                    let r = Self::build(rest);
 
                     let bb = AABB::surrounding(l.bounding_box(), r.bounding_box());
 
@@ -65,6 +217,8 @@ impl BVH {
             left,
             right,
             bounding_box,
+            fingerprint: 0,
+            lights: Vec::new(),
         }
     }
 }
@@ -74,7 +228,9 @@ impl Intersectable for BVH {
         self.bounding_box
     }
 
-    fn intersect(&self, ray: &Ray, min: f64, max: f64) -> Option<Intersection> {
+    fn intersect(&self, ray: &Ray, min: f64, max: f64) -> Option<Intersection<'_>> {
+        count_intersection_test();
+
         if !self.bounding_box.intersect(ray, min, max) {
             return None;
         }
@@ -105,3 +261,44 @@ impl Intersectable for BVH {
         }
     }
 }
+
+/// Object of the hierarchy tagging its intersections with its index in the
+/// list the hierarchy was built from, and with the hash of its material if it
+/// has a single one. Objects with several materials, like a nested `BVH`, tag
+/// their intersections themselves.
+#[derive(Clone, Debug)]
+struct Indexed {
+    index: u32,
+    material_id: Option<u64>,
+    object: Arc<dyn Intersectable + Send + Sync>,
+}
+
+impl Intersectable for Indexed {
+    fn bounding_box(&self) -> AABB {
+        self.object.bounding_box()
+    }
+
+    fn material(&self) -> Option<&Material> {
+        self.object.material()
+    }
+
+    fn area(&self) -> f64 {
+        self.object.area()
+    }
+
+    fn sample_surface(&self, sampler: &mut Sampler) -> Option<Vec3> {
+        self.object.sample_surface(sampler)
+    }
+
+    fn intersect(&self, ray: &Ray, min: f64, max: f64) -> Option<Intersection<'_>> {
+        count_intersection_test();
+
+        self.object
+            .intersect(ray, min, max)
+            .map(|intersection| Intersection {
+                object_id: self.index,
+                material_id: self.material_id.unwrap_or(intersection.material_id),
+                ..intersection
+            })
+    }
+}
@@ -1,6 +1,7 @@
 //! Camera module.
 
-use super::ray::Ray;
+use super::ray::{Ray, RayDifferential};
+use super::Error;
 use super::Vec3;
 
 /// The objct that defines where to look from and where to inside the scene.
@@ -26,7 +27,26 @@ impl Camera {
     /// * `aspect_ratio` - Aspect ratio of the image. Usually width/height.
     /// * `roll` - Angle of rotation on the z (view) coordinate (roll to a side).
     ///
-    pub fn new(look_from: Vec3, look_at: Vec3, fov: f64, aspect_ratio: f64, roll: f64) -> Self {
+    /// Fails if `look_from` and `look_at` are the same point, the view
+    /// direction is parallel to the (rolled) up vector, `fov` is not between 0
+    /// and 180 degrees or `aspect_ratio` is not positive.
+    pub fn new(
+        look_from: Vec3,
+        look_at: Vec3,
+        fov: f64,
+        aspect_ratio: f64,
+        roll: f64,
+    ) -> Result<Self, Error> {
+        if !(fov > 0.0 && fov < 180.0) {
+            return Err(Error::InvalidCamera(
+                "field of view must be between 0 and 180 degrees",
+            ));
+        }
+
+        if !(aspect_ratio > 0.0 && aspect_ratio.is_finite()) {
+            return Err(Error::InvalidCamera("aspect ratio must be positive"));
+        }
+
         let roll_angle = roll.to_radians();
         let rotated_up = Vec3::new(-roll_angle.sin(), roll_angle.cos(), 0.0);
 
@@ -34,6 +54,18 @@ impl Camera {
         let u = rotated_up.cross(w).normalize();
         let v = w.cross(u);
 
+        if !w.is_finite() {
+            return Err(Error::InvalidCamera(
+                "look from and look at must be different points",
+            ));
+        }
+
+        if !u.is_finite() {
+            return Err(Error::InvalidCamera(
+                "view direction can't be parallel to the up vector",
+            ));
+        }
+
         let half_height = (fov.to_radians() / 2.0).tan();
         let half_width = half_height * aspect_ratio;
 
@@ -41,7 +73,7 @@ impl Camera {
         let horizontal = u * (2.0 * half_width);
         let vertical = -v * (2.0 * half_height);
 
-        Camera {
+        Ok(Camera {
             look_from,
             corner,
             horizontal,
@@ -49,7 +81,7 @@ impl Camera {
             u,
             v,
             w,
-        }
+        })
     }
 
     /// Generate a direction ray from the camera.
@@ -62,11 +94,35 @@ impl Camera {
     /// *Note*: Screen coordinates are assumed to be between 0.0 and 1.0
     /// inclusive.
     pub fn get_ray(&self, u: f64, v: f64) -> Ray {
-        let direction = self.corner + self.horizontal * u + self.vertical * v - self.look_from;
+        Ray::new(self.look_from, self.direction(u, v))
+    }
 
+    /// Generate a direction ray from the camera carrying ray differentials.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - horizontal screen coordinate.
+    /// * `v` - vertical screen coordinate.
+    /// * `du` - horizontal distance in screen coordinates to the neighbor ray.
+    /// * `dv` - vertical distance in screen coordinates to the neighbor ray.
+    ///
+    /// *Note*: `du` and `dv` are usually the size of a pixel in screen
+    /// coordinates, that is `1.0 / width` and `1.0 / height`.
+    pub fn get_ray_differential(&self, u: f64, v: f64, du: f64, dv: f64) -> Ray {
         Ray {
             origin: self.look_from,
-            direction,
+            direction: self.direction(u, v),
+            differential: Some(RayDifferential {
+                rx_origin: self.look_from,
+                rx_direction: self.direction(u + du, v),
+                ry_origin: self.look_from,
+                ry_direction: self.direction(u, v + dv),
+            }),
+            wavelength: None,
         }
     }
+
+    fn direction(&self, u: f64, v: f64) -> Vec3 {
+        self.corner + self.horizontal * u + self.vertical * v - self.look_from
+    }
 }
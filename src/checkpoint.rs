@@ -0,0 +1,350 @@
+//! Checkpoint files, the accumulated samples of a render saved to disk so it
+//! can be resumed later.
+//!
+//! The file is a little endian binary file with a header followed by the sums
+//! of every pixel, row by row, and then by the sums of every AOV layer.
+
+use crate::aov::Aov;
+use crate::film::{Film, Layer, Pixel};
+use crate::{Color, Error, Filter, Integrator, RenderSettings, SamplerKind};
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"PTCHKPT4";
+
+/// What a checkpoint was rendered from and how far it got.
+///
+/// # Members
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `max_depth` - Ray bouncing limit the samples were taken with.
+/// * `samples` - Samples per pixel taken so far.
+/// * `fingerprint` - Fingerprint of the scene, see `Scene::fingerprint`.
+/// * `aovs` - AOVs rendered along with the image.
+/// * `integrator` - How the color of the samples was computed.
+/// * `filter` - Filter the samples were splatted with.
+/// * `sampler` - Sampler the samples were taken with.
+/// * `seed` - Seed of the random numbers of the samples.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub width: u32,
+    pub height: u32,
+    pub max_depth: u32,
+    pub samples: u32,
+    pub fingerprint: u64,
+    pub aovs: Vec<Aov>,
+    pub integrator: Integrator,
+    pub filter: Filter,
+    pub sampler: SamplerKind,
+    pub seed: u64,
+}
+
+impl Header {
+    /// Header of a render of the scene with `fingerprint` made with
+    /// `settings`, `samples` per pixel into it.
+    pub fn new(settings: &RenderSettings, fingerprint: u64, samples: u32) -> Self {
+        Header {
+            width: settings.width,
+            height: settings.height,
+            max_depth: settings.max_depth,
+            samples,
+            fingerprint,
+            aovs: settings.film_aovs(),
+            integrator: settings.integrator,
+            filter: settings.filter,
+            sampler: settings.sampler,
+            seed: settings.seed,
+        }
+    }
+}
+
+const SAMPLERS: [SamplerKind; 4] = [
+    SamplerKind::Independent,
+    SamplerKind::Stratified,
+    SamplerKind::Halton,
+    SamplerKind::Sobol,
+];
+
+/// Saves the film to `path`.
+///
+/// The checkpoint is written next to `path` first and then moved over it so
+/// a render killed while saving doesn't destroy the previous checkpoint.
+pub fn write(path: &Path, header: &Header, film: &Film) -> Result<(), Error> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+
+    let io_error = |source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let file = File::create(&temporary).map_err(io_error)?;
+    let mut writer = BufWriter::new(file);
+
+    write_film(&mut writer, header, film)
+        .and_then(|_| writer.flush())
+        .map_err(io_error)?;
+
+    drop(writer);
+
+    fs::rename(&temporary, path).map_err(io_error)
+}
+
+/// Loads a film saved with `write`.
+pub fn read(path: &Path) -> Result<(Header, Film), Error> {
+    let file = File::open(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    read_film(&mut BufReader::new(file)).map_err(|source| {
+        if source.kind() == std::io::ErrorKind::InvalidData {
+            Error::InvalidCheckpoint("not a checkpoint file")
+        } else if source.kind() == std::io::ErrorKind::UnexpectedEof {
+            Error::InvalidCheckpoint("truncated file")
+        } else {
+            Error::Io {
+                path: path.to_path_buf(),
+                source,
+            }
+        }
+    })
+}
+
+fn write_film<W: Write>(writer: &mut W, header: &Header, film: &Film) -> std::io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&header.width.to_le_bytes())?;
+    writer.write_all(&header.height.to_le_bytes())?;
+    writer.write_all(&header.max_depth.to_le_bytes())?;
+    writer.write_all(&header.samples.to_le_bytes())?;
+    writer.write_all(&header.fingerprint.to_le_bytes())?;
+    writer.write_all(&(header.aovs.len() as u32).to_le_bytes())?;
+
+    for aov in &header.aovs {
+        writer.write_all(&[*aov as u8])?;
+    }
+
+    write_integrator(writer, &header.integrator)?;
+    write_filter(writer, &header.filter)?;
+
+    let sampler = SAMPLERS
+        .iter()
+        .position(|&sampler| sampler == header.sampler)
+        .unwrap_or_default();
+
+    writer.write_all(&[sampler as u8])?;
+    writer.write_all(&header.seed.to_le_bytes())?;
+
+    for pixel in film.pixels() {
+        for value in [
+            pixel.weighted_sum.r,
+            pixel.weighted_sum.g,
+            pixel.weighted_sum.b,
+            pixel.weight_sum,
+            pixel.luminance_sum,
+            pixel.luminance_squared_sum,
+        ] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+
+        writer.write_all(&pixel.samples.to_le_bytes())?;
+    }
+
+    for layer in film.layers() {
+        for sum in &layer.sums {
+            for value in [sum.r, sum.g, sum.b] {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_film<R: Read>(reader: &mut R) -> std::io::Result<(Header, Film)> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+
+    if &magic != MAGIC {
+        return Err(std::io::ErrorKind::InvalidData.into());
+    }
+
+    let width = read_u32(reader)?;
+    let height = read_u32(reader)?;
+    let max_depth = read_u32(reader)?;
+    let samples = read_u32(reader)?;
+    let fingerprint = read_u64(reader)?;
+    let mut aovs = Vec::new();
+
+    for _ in 0..read_u32(reader)? {
+        let aov = Aov::ALL
+            .get(usize::from(read_u8(reader)?))
+            .ok_or(std::io::ErrorKind::InvalidData)?;
+
+        aovs.push(*aov);
+    }
+
+    let integrator = read_integrator(reader)?;
+    let filter = read_filter(reader)?;
+    let sampler = *SAMPLERS
+        .get(usize::from(read_u8(reader)?))
+        .ok_or(std::io::ErrorKind::InvalidData)?;
+
+    let header = Header {
+        width,
+        height,
+        max_depth,
+        samples,
+        fingerprint,
+        aovs,
+        integrator,
+        filter,
+        sampler,
+        seed: read_u64(reader)?,
+    };
+
+    let count = u64::from(header.width) * u64::from(header.height);
+    let mut pixels = Vec::new();
+
+    for _ in 0..count {
+        let weighted_sum = Color::new(read_f64(reader)?, read_f64(reader)?, read_f64(reader)?);
+
+        pixels.push(Pixel {
+            weighted_sum,
+            weight_sum: read_f64(reader)?,
+            luminance_sum: read_f64(reader)?,
+            luminance_squared_sum: read_f64(reader)?,
+            samples: read_u32(reader)?,
+        });
+    }
+
+    let mut layers = Vec::new();
+
+    for &aov in &header.aovs {
+        let mut sums = Vec::new();
+
+        for _ in 0..count {
+            sums.push(Color::new(
+                read_f64(reader)?,
+                read_f64(reader)?,
+                read_f64(reader)?,
+            ));
+        }
+
+        layers.push(Layer { aov, sums });
+    }
+
+    let film = Film::from_pixels(header.width, header.height, pixels, layers)
+        .ok_or(std::io::ErrorKind::InvalidData)?;
+
+    Ok((header, film))
+}
+
+/// Writes the integrator as a code followed by its parameter, if any.
+fn write_integrator<W: Write>(writer: &mut W, integrator: &Integrator) -> std::io::Result<()> {
+    let (code, parameter) = match *integrator {
+        Integrator::PathTracer => (0, 0.0),
+        Integrator::Spectral => (1, 0.0),
+        Integrator::Normals => (2, 0.0),
+        Integrator::Depth { max_distance } => (3, max_distance),
+        Integrator::Uv => (4, 0.0),
+        Integrator::MaterialId => (5, 0.0),
+        Integrator::AmbientOcclusion { distance } => (6, distance),
+        Integrator::BvhHeatmap { max_tests } => (7, f64::from(max_tests)),
+    };
+
+    writer.write_all(&[code])?;
+    writer.write_all(&parameter.to_le_bytes())
+}
+
+fn read_integrator<R: Read>(reader: &mut R) -> std::io::Result<Integrator> {
+    let code = read_u8(reader)?;
+    let parameter = read_f64(reader)?;
+
+    Ok(match code {
+        0 => Integrator::PathTracer,
+        1 => Integrator::Spectral,
+        2 => Integrator::Normals,
+        3 => Integrator::Depth {
+            max_distance: parameter,
+        },
+        4 => Integrator::Uv,
+        5 => Integrator::MaterialId,
+        6 => Integrator::AmbientOcclusion {
+            distance: parameter,
+        },
+        7 => Integrator::BvhHeatmap {
+            max_tests: parameter as u32,
+        },
+        _ => return Err(std::io::ErrorKind::InvalidData.into()),
+    })
+}
+
+/// Writes the filter as a code followed by its radius and two more
+/// parameters, zero for filters that don't have them.
+fn write_filter<W: Write>(writer: &mut W, filter: &Filter) -> std::io::Result<()> {
+    let (code, parameters) = match *filter {
+        Filter::Box { radius } => (0, [radius, 0.0, 0.0]),
+        Filter::Tent { radius } => (1, [radius, 0.0, 0.0]),
+        Filter::Gaussian { radius, sigma } => (2, [radius, sigma, 0.0]),
+        Filter::Mitchell { radius, b, c } => (3, [radius, b, c]),
+        Filter::BlackmanHarris { radius } => (4, [radius, 0.0, 0.0]),
+    };
+
+    writer.write_all(&[code])?;
+
+    for parameter in parameters {
+        writer.write_all(&parameter.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn read_filter<R: Read>(reader: &mut R) -> std::io::Result<Filter> {
+    let code = read_u8(reader)?;
+    let radius = read_f64(reader)?;
+    let first = read_f64(reader)?;
+    let second = read_f64(reader)?;
+
+    Ok(match code {
+        0 => Filter::Box { radius },
+        1 => Filter::Tent { radius },
+        2 => Filter::Gaussian {
+            radius,
+            sigma: first,
+        },
+        3 => Filter::Mitchell {
+            radius,
+            b: first,
+            c: second,
+        },
+        4 => Filter::BlackmanHarris { radius },
+        _ => return Err(std::io::ErrorKind::InvalidData.into()),
+    })
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> std::io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> std::io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> std::io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_f64<R: Read>(reader: &mut R) -> std::io::Result<f64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
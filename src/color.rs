@@ -1,4 +1,6 @@
 //! Color module.
+use crate::spectrum;
+
 use std::ops::{Add, AddAssign, Div, Mul};
 
 /// Color object.
@@ -8,7 +10,7 @@ use std::ops::{Add, AddAssign, Div, Mul};
 /// and 1.0 and they're clamped when converted to `Rgb` it doens't mean they
 /// can't be declared to have larger values if needed to. This is usually the
 /// case for light intensity.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct Color {
     pub r: f64,
     pub g: f64,
@@ -28,19 +30,80 @@ impl Color {
         }
     }
 
+    /// Color of the light emitted by a blackbody at `temperature` Kelvin,
+    /// like the filament of a bulb or a star, scaled to a luminance of 1.0.
+    /// Candle light is around 1900K, daylight around 6500K. Black if the
+    /// temperature isn't positive.
+    pub fn blackbody(temperature: f64) -> Self {
+        if !(temperature > 0.0 && temperature.is_finite()) {
+            return Color::default();
+        }
+
+        let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+        let mut wavelength = spectrum::MIN_WAVELENGTH;
+
+        while wavelength <= spectrum::MAX_WAVELENGTH {
+            let radiance = spectrum::planck(wavelength, temperature);
+            let (cx, cy, cz) = spectrum::cie_xyz(wavelength);
+
+            x += radiance * cx;
+            y += radiance * cy;
+            z += radiance * cz;
+            wavelength += 1.0;
+        }
+
+        if !(y > 0.0 && y.is_finite()) {
+            return Color::default();
+        }
+
+        let color = spectrum::xyz_to_rgb(x / y, 1.0, z / y);
+
+        // Colors out of the sRGB gamut are clipped, which changes their
+        // luminance a bit.
+        Color::new(color.r.max(0.0), color.g.max(0.0), color.b.max(0.0))
+    }
+
+    /// Absorption coefficient of a medium that turns white light into this
+    /// color after traveling `distance` through it, see
+    /// `Material::absorbing_dielectric`.
+    pub fn absorption_at_distance(&self, distance: f64) -> Self {
+        let coefficient = |c: f64| -c.clamp(1e-6, 1.0).ln() / distance;
+
+        Color::new(
+            coefficient(self.r),
+            coefficient(self.g),
+            coefficient(self.b),
+        )
+    }
+
+    /// Fraction of the light left after traveling `distance` through a medium
+    /// whose absorption coefficient is this color.
+    pub fn transmittance(&self, distance: f64) -> Self {
+        Color::new(
+            (-self.r * distance).exp(),
+            (-self.g * distance).exp(),
+            (-self.b * distance).exp(),
+        )
+    }
+
+    /// Relative luminance of the color (Rec. 709 primaries).
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
     pub fn to_rgb(&self) -> image::Rgb<u8> {
         image::Rgb([
-            (self.r.min(1.0).max(0.0) * 255.0) as u8,
-            (self.g.min(1.0).max(0.0) * 255.0) as u8,
-            (self.b.min(1.0).max(0.0) * 255.0) as u8,
+            (self.r.clamp(0.0, 1.0) * 255.0) as u8,
+            (self.g.clamp(0.0, 1.0) * 255.0) as u8,
+            (self.b.clamp(0.0, 1.0) * 255.0) as u8,
         ])
     }
 
     pub fn to_gamma_rgb(&self, gamma_correction: f64) -> image::Rgb<u8> {
         image::Rgb([
-            (self.r.min(1.0).max(0.0).powf(gamma_correction) * 255.0) as u8,
-            (self.g.min(1.0).max(0.0).powf(gamma_correction) * 255.0) as u8,
-            (self.b.min(1.0).max(0.0).powf(gamma_correction) * 255.0) as u8,
+            (self.r.clamp(0.0, 1.0).powf(gamma_correction) * 255.0) as u8,
+            (self.g.clamp(0.0, 1.0).powf(gamma_correction) * 255.0) as u8,
+            (self.b.clamp(0.0, 1.0).powf(gamma_correction) * 255.0) as u8,
         ])
     }
 }
@@ -0,0 +1,114 @@
+//! Denoiser, an edge avoiding à-trous wavelet filter guided by the albedo and
+//! normal of the first hit of every pixel.
+//!
+//! From Dammertz et al., "Edge-Avoiding À-Trous Wavelet Transform for fast
+//! Global Illumination Filtering". The image is blurred several times with a
+//! 5x5 kernel whose taps are spread twice as far apart every iteration. Each
+//! tap is weighted down by how much its color, albedo and normal differ from
+//! the pixel being filtered, so the blur stops at edges and texture detail.
+
+use crate::settings::Denoise;
+
+use image::Rgb32FImage;
+
+/// Weights of the taps of the B3 spline kernel, from the center outwards.
+const KERNEL: [f32; 3] = [3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+/// Albedo below which lighting isn't divided by the albedo.
+const MIN_ALBEDO: f32 = 1e-3;
+
+/// Filters `color` with the parameters in `settings`. The images must have
+/// the same dimensions.
+pub fn a_trous(
+    color: &Rgb32FImage,
+    albedo: &Rgb32FImage,
+    normal: &Rgb32FImage,
+    settings: &Denoise,
+) -> Rgb32FImage {
+    let (width, height) = color.dimensions();
+    let albedo_at = |x: u32, y: u32| albedo.get_pixel(x, y).0.map(|a| a.max(MIN_ALBEDO));
+
+    // Filter the light reaching the surfaces rather than the color, so the
+    // textures aren't blurred along with the noise.
+    let mut current = Rgb32FImage::from_fn(width, height, |x, y| {
+        let c = color.get_pixel(x, y).0;
+        let a = albedo_at(x, y);
+
+        image::Rgb([c[0] / a[0], c[1] / a[1], c[2] / a[2]])
+    });
+
+    for iteration in 0..settings.iterations {
+        let step = 1i64 << iteration;
+        // Noise is smoothed out by each iteration, so later ones can be
+        // stricter about color differences.
+        let color_sigma = settings.color_sigma as f32 / (1 << iteration) as f32;
+
+        let next = Rgb32FImage::from_fn(width, height, |x, y| {
+            let center = tonemap(current.get_pixel(x, y).0);
+            let center_albedo = albedo.get_pixel(x, y).0;
+            let center_normal = normal.get_pixel(x, y).0;
+
+            let mut sum = [0.0; 3];
+            let mut weight_sum = 0.0;
+
+            for dy in -2i64..=2 {
+                for dx in -2i64..=2 {
+                    let qx = i64::from(x) + dx * step;
+                    let qy = i64::from(y) + dy * step;
+
+                    if qx < 0 || qy < 0 || qx >= i64::from(width) || qy >= i64::from(height) {
+                        continue;
+                    }
+
+                    let (qx, qy) = (qx as u32, qy as u32);
+                    let value = current.get_pixel(qx, qy).0;
+
+                    let weight = KERNEL[dx.unsigned_abs() as usize]
+                        * KERNEL[dy.unsigned_abs() as usize]
+                        * edge_stop(center, tonemap(value), color_sigma)
+                        * edge_stop(
+                            center_albedo,
+                            albedo.get_pixel(qx, qy).0,
+                            settings.albedo_sigma as f32,
+                        )
+                        * edge_stop(
+                            center_normal,
+                            normal.get_pixel(qx, qy).0,
+                            settings.normal_sigma as f32,
+                        );
+
+                    for (sum, value) in sum.iter_mut().zip(value) {
+                        *sum += value * weight;
+                    }
+
+                    weight_sum += weight;
+                }
+            }
+
+            // The center tap always has a weight of at least KERNEL[0]².
+            image::Rgb(sum.map(|sum| sum / weight_sum))
+        });
+
+        current = next;
+    }
+
+    Rgb32FImage::from_fn(width, height, |x, y| {
+        let c = current.get_pixel(x, y).0;
+        let a = albedo_at(x, y);
+
+        image::Rgb([c[0] * a[0], c[1] * a[1], c[2] * a[2]])
+    })
+}
+
+/// Weight of a tap whose feature is `b` for a pixel whose feature is `a`.
+fn edge_stop(a: [f32; 3], b: [f32; 3], sigma: f32) -> f32 {
+    let distance = (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2);
+
+    (-distance / (sigma * sigma)).exp()
+}
+
+/// Compresses lighting into [0, 1) so color differences don't depend on how
+/// bright the scene is.
+fn tonemap(c: [f32; 3]) -> [f32; 3] {
+    c.map(|c| c.max(0.0) / (1.0 + c.max(0.0)))
+}
@@ -0,0 +1,85 @@
+//! Error module.
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// Errors reported by the path tracer.
+#[derive(Debug)]
+pub enum Error {
+    /// A texture image could not be opened or decoded.
+    TextureLoad {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    /// The rendered image could not be written.
+    ImageSave {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    /// A file could not be read or written.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A checkpoint file is corrupt or doesn't belong to the scene and
+    /// settings being rendered.
+    InvalidCheckpoint(&'static str),
+    /// Partial renders being merged don't belong to the same scene, image
+    /// size or settings.
+    MismatchedPartials(&'static str),
+    /// The scene has no objects.
+    EmptyScene,
+    /// The object at `index` has a bounding box with NaN or infinite
+    /// coordinates.
+    InvalidBoundingBox { index: usize },
+    /// A shape broke one of the invariants of `Intersectable`, see
+    /// `shape::validate`.
+    InvalidShape(&'static str),
+    /// The camera parameters don't define a valid view.
+    InvalidCamera(&'static str),
+    /// The render parameters are out of range.
+    InvalidSettings(&'static str),
+    /// A render worker panicked, the image is incomplete.
+    WorkerPanicked,
+    /// The images given to the denoiser have different dimensions.
+    MismatchedImages,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::TextureLoad { path, source } => {
+                write!(f, "can't load texture {}: {}", path.display(), source)
+            }
+            Error::ImageSave { path, source } => {
+                write!(f, "can't save image {}: {}", path.display(), source)
+            }
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::InvalidCheckpoint(reason) => write!(f, "invalid checkpoint: {}", reason),
+            Error::MismatchedPartials(reason) => {
+                write!(f, "can't merge partial renders: {}", reason)
+            }
+            Error::EmptyScene => write!(f, "the scene has no objects"),
+            Error::InvalidBoundingBox { index } => {
+                write!(f, "object {} has a non finite bounding box", index)
+            }
+            Error::InvalidShape(reason) => write!(f, "invalid shape: {}", reason),
+            Error::InvalidCamera(reason) => write!(f, "invalid camera: {}", reason),
+            Error::InvalidSettings(reason) => write!(f, "invalid render settings: {}", reason),
+            Error::WorkerPanicked => write!(f, "a render worker panicked"),
+            Error::MismatchedImages => {
+                write!(f, "the images to denoise have different dimensions")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::TextureLoad { source, .. } | Error::ImageSave { source, .. } => Some(source),
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,400 @@
+//! Film module, where the radiance samples of every pixel are accumulated.
+
+use crate::aov::Aov;
+use crate::denoise;
+use crate::filter::Filter;
+use crate::settings::Denoise;
+use crate::tile::Tile;
+use crate::Color;
+use crate::Error;
+
+use image::{GrayImage, Rgb32FImage, RgbImage};
+
+use std::time::Duration;
+
+/// Running sums of the samples taken for a pixel.
+///
+/// The color is the filter weighted sum of the samples splatted on the pixel,
+/// which includes samples taken in neighbor pixels. The luminance sums and
+/// the sample count only cover the samples taken in the pixel itself and are
+/// used to estimate how far the pixel is from converging.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Pixel {
+    pub weighted_sum: Color,
+    pub weight_sum: f64,
+    pub luminance_sum: f64,
+    pub luminance_squared_sum: f64,
+    pub samples: u32,
+}
+
+impl Pixel {
+    /// Accounts for a sample taken in this pixel. The color still has to be
+    /// splatted with `add_splat`.
+    pub fn add_sample(&mut self, color: Color) {
+        let luminance = color.luminance();
+
+        self.luminance_sum += luminance;
+        self.luminance_squared_sum += luminance * luminance;
+        self.samples += 1;
+    }
+
+    /// Adds a sample `weight` times to the color of the pixel.
+    pub fn add_splat(&mut self, color: Color, weight: f64) {
+        self.weighted_sum += color * weight;
+        self.weight_sum += weight;
+    }
+
+    pub fn merge(&mut self, other: &Pixel) {
+        self.weighted_sum += other.weighted_sum;
+        self.weight_sum += other.weight_sum;
+        self.luminance_sum += other.luminance_sum;
+        self.luminance_squared_sum += other.luminance_squared_sum;
+        self.samples += other.samples;
+    }
+
+    /// Weighted mean of the samples splatted so far.
+    pub fn color(&self) -> Color {
+        if self.weight_sum <= 0.0 {
+            Color::default()
+        } else {
+            self.weighted_sum / self.weight_sum
+        }
+    }
+
+    /// Standard error of the mean luminance relative to the mean itself.
+    ///
+    /// Pixels with less than two samples have an infinite error.
+    pub fn relative_error(&self) -> f64 {
+        if self.samples < 2 {
+            return f64::INFINITY;
+        }
+
+        let n = f64::from(self.samples);
+        let mean = self.luminance_sum / n;
+        let variance =
+            ((self.luminance_squared_sum - mean * self.luminance_sum) / (n - 1.0)).max(0.0);
+
+        (variance / n).sqrt() / mean.max(1e-3)
+    }
+}
+
+/// State of the image after a render pass.
+///
+/// # Members
+/// * `image` - Gamma corrected image of the samples taken so far.
+/// * `samples` - Samples per pixel taken so far. Pixels that converged with
+///   adaptive sampling have less.
+/// * `elapsed` - Time since the render started.
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub image: RgbImage,
+    pub samples: u32,
+    pub elapsed: Duration,
+}
+
+/// Filter weighted sums of an AOV for every pixel of a film, row by row.
+///
+/// Divided by the weight sums of the film pixels they give the AOV image.
+#[derive(Clone, Debug)]
+pub struct Layer {
+    pub aov: Aov,
+    pub sums: Vec<Color>,
+}
+
+/// Grid of pixels with the accumulated samples of the whole image, and of
+/// the AOVs being rendered along with it.
+#[derive(Clone, Debug)]
+pub struct Film {
+    width: u32,
+    height: u32,
+    pixels: Vec<Pixel>,
+    layers: Vec<Layer>,
+}
+
+impl Film {
+    /// Creates an empty film.
+    ///
+    /// Fails if `width * height` pixels don't fit in memory.
+    pub fn new(width: u32, height: u32, aovs: &[Aov]) -> Result<Self, Error> {
+        let size = pixel_count(width, height)
+            .ok_or(Error::InvalidSettings("image dimensions are too large"))?;
+
+        Ok(Film {
+            width,
+            height,
+            pixels: vec![Pixel::default(); size],
+            layers: aovs
+                .iter()
+                .map(|&aov| Layer {
+                    aov,
+                    sums: vec![Color::default(); size],
+                })
+                .collect(),
+        })
+    }
+
+    /// Creates a film from its pixels and AOV layers, row by row. `None` if
+    /// there aren't exactly `width * height` of them.
+    pub fn from_pixels(
+        width: u32,
+        height: u32,
+        pixels: Vec<Pixel>,
+        layers: Vec<Layer>,
+    ) -> Option<Self> {
+        let size = pixel_count(width, height)?;
+
+        if pixels.len() == size && layers.iter().all(|layer| layer.sums.len() == size) {
+            Some(Film {
+                width,
+                height,
+                pixels,
+                layers,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn pixels(&self) -> &[Pixel] {
+        &self.pixels
+    }
+
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// The AOVs of the layers, in order.
+    pub fn aovs(&self) -> Vec<Aov> {
+        self.layers.iter().map(|layer| layer.aov).collect()
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> &Pixel {
+        &self.pixels[self.index(x, y)]
+    }
+
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> &mut Pixel {
+        let index = self.index(x, y);
+
+        &mut self.pixels[index]
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Adds the samples of a tile.
+    ///
+    /// Tiles overlap where samples were splatted past their edges. Floating
+    /// point addition isn't associative, so for a render to be reproducible
+    /// they have to be merged in the same order every time.
+    pub fn merge_tile(&mut self, tile: &FilmTile) {
+        for ((x, y), pixel) in tile.bounds.pixels().zip(&tile.pixels) {
+            self.pixel_mut(x, y).merge(pixel);
+        }
+
+        let width = self.width as usize;
+
+        for (layer, sums) in self.layers.iter_mut().zip(&tile.layers) {
+            for ((x, y), sum) in tile.bounds.pixels().zip(sums) {
+                layer.sums[y as usize * width + x as usize] += *sum;
+            }
+        }
+    }
+
+    /// Most samples taken by any pixel.
+    pub fn max_samples(&self) -> u32 {
+        self.pixels.iter().map(|p| p.samples).max().unwrap_or(0)
+    }
+
+    /// Adds the samples of another film of the same size and AOVs.
+    pub fn merge(&mut self, other: &Film) {
+        for (pixel, other) in self.pixels.iter_mut().zip(&other.pixels) {
+            pixel.merge(other);
+        }
+
+        for (layer, other) in self.layers.iter_mut().zip(&other.layers) {
+            for (sum, other) in layer.sums.iter_mut().zip(&other.sums) {
+                *sum += *other;
+            }
+        }
+    }
+
+    /// Whether each pixel, row by row, took samples of its own. Pixels outside
+    /// of the region of a partial render don't, even if samples of the region
+    /// were splatted into them.
+    pub fn sampled_pixels(&self) -> impl Iterator<Item = bool> + '_ {
+        self.pixels.iter().map(|pixel| pixel.samples > 0)
+    }
+
+    /// Whether every pixel got a sample splatted into it.
+    pub fn is_covered(&self) -> bool {
+        self.pixels.iter().all(|pixel| pixel.weight_sum != 0.0)
+    }
+
+    /// Gamma corrected image of the mean of every pixel.
+    pub fn to_image(&self, gamma_correction: f64) -> RgbImage {
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            self.pixel(x, y).color().to_gamma_rgb(gamma_correction)
+        })
+    }
+
+    /// Image of the mean of every pixel, without gamma correction.
+    pub fn to_linear_image(&self) -> Rgb32FImage {
+        Rgb32FImage::from_fn(self.width, self.height, |x, y| {
+            let color = self.pixel(x, y).color();
+
+            image::Rgb([color.r as f32, color.g as f32, color.b as f32])
+        })
+    }
+
+    /// Gamma corrected image of the mean of every pixel, denoised with the
+    /// albedo and normal layers as a guide. Not denoised if the film doesn't
+    /// have those layers.
+    pub fn to_denoised_image(&self, denoise: &Denoise, gamma_correction: f64) -> RgbImage {
+        let layer = |aov| self.layers.iter().position(|layer| layer.aov == aov);
+
+        let (albedo, normal) = match (layer(Aov::Albedo), layer(Aov::Normal)) {
+            (Some(albedo), Some(normal)) => (albedo, normal),
+            _ => return self.to_image(gamma_correction),
+        };
+
+        let denoised = denoise::a_trous(
+            &self.to_linear_image(),
+            &self.aov_image(albedo),
+            &self.aov_image(normal),
+            denoise,
+        );
+
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            let [r, g, b] = denoised.get_pixel(x, y).0;
+
+            Color::new(f64::from(r), f64::from(g), f64::from(b)).to_gamma_rgb(gamma_correction)
+        })
+    }
+
+    /// Image of the AOV of the layer at `index`, with its values as they are.
+    pub fn aov_image(&self, index: usize) -> Rgb32FImage {
+        let layer = &self.layers[index];
+
+        Rgb32FImage::from_fn(self.width, self.height, |x, y| {
+            let index = self.index(x, y);
+            let weight_sum = self.pixels[index].weight_sum;
+
+            let value = if weight_sum <= 0.0 {
+                Color::default()
+            } else {
+                layer.sums[index] / weight_sum
+            };
+
+            image::Rgb([value.r as f32, value.g as f32, value.b as f32])
+        })
+    }
+
+    /// Image of how many samples every pixel took, white being the pixel with
+    /// the most samples.
+    pub fn sample_map(&self) -> GrayImage {
+        let max = self.max_samples().max(1);
+
+        GrayImage::from_fn(self.width, self.height, |x, y| {
+            let samples = self.pixel(x, y).samples;
+
+            image::Luma([(f64::from(samples) / f64::from(max) * 255.0) as u8])
+        })
+    }
+}
+
+/// Pixels a tile job takes samples for, plus the border of neighbor pixels
+/// its samples are splatted on.
+///
+/// # Members
+/// * `bounds` - The tile and its border, clipped to the image.
+/// * `pixels` - Pixels of `bounds`, row by row.
+/// * `layers` - Weighted sums of each AOV of the film for the pixels of
+///   `bounds`, row by row.
+#[derive(Clone, Debug)]
+pub struct FilmTile {
+    pub bounds: Tile,
+    pub pixels: Vec<Pixel>,
+    pub layers: Vec<Vec<Color>>,
+}
+
+impl FilmTile {
+    /// Creates an empty film tile for `tile` of an image of `width` x
+    /// `height` pixels with `layers` AOVs, large enough for the samples
+    /// splatted with `filter`.
+    pub fn new(tile: Tile, filter: &Filter, width: u32, height: u32, layers: usize) -> Self {
+        let border = (filter.radius() + 0.5).floor() as u32;
+        let bounds = tile.grow(border, width, height);
+
+        FilmTile {
+            bounds,
+            pixels: vec![Pixel::default(); bounds.area()],
+            layers: vec![vec![Color::default(); bounds.area()]; layers],
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y - self.bounds.y) as usize * self.bounds.width as usize + (x - self.bounds.x) as usize
+    }
+
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> &mut Pixel {
+        let index = self.index(x, y);
+
+        &mut self.pixels[index]
+    }
+
+    /// Adds a sample taken at `x`, `y` in image coordinates to every pixel in
+    /// reach of the filter, along with its value of each AOV.
+    pub fn splat(&mut self, filter: &Filter, x: f64, y: f64, color: Color, aovs: &[Color]) {
+        let radius = filter.radius();
+        let Tile {
+            x: left,
+            y: top,
+            width,
+            height,
+        } = self.bounds;
+
+        let x0 = (x - radius - 0.5).ceil().max(f64::from(left)) as u32;
+        let x1 = (x + radius - 0.5).floor().min(f64::from(left + width - 1)) as u32;
+        let y0 = (y - radius - 0.5).ceil().max(f64::from(top)) as u32;
+        let y1 = (y + radius - 0.5).floor().min(f64::from(top + height - 1)) as u32;
+
+        for py in y0..=y1 {
+            for px in x0..=x1 {
+                let weight = filter.evaluate(f64::from(px) + 0.5 - x, f64::from(py) + 0.5 - y);
+
+                if weight != 0.0 {
+                    let index = self.index(px, py);
+
+                    self.pixels[index].add_splat(color, weight);
+
+                    for (layer, value) in self.layers.iter_mut().zip(aovs) {
+                        layer[index] += *value * weight;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Samples taken for the pixels of the tile.
+    pub fn samples(&self) -> u64 {
+        self.pixels.iter().map(|p| u64::from(p.samples)).sum()
+    }
+}
+
+/// Number of pixels of a `width` by `height` image, `None` if they couldn't
+/// be addressed in memory.
+fn pixel_count(width: u32, height: u32) -> Option<usize> {
+    let count = (width as usize).checked_mul(height as usize)?;
+    let bytes = count.checked_mul(std::mem::size_of::<Pixel>())?;
+
+    (bytes <= isize::MAX as usize).then_some(count)
+}
@@ -0,0 +1,91 @@
+//! Pixel reconstruction filters.
+
+use std::f64::consts::PI;
+
+/// Filter used to reconstruct the pixels of the image from the samples.
+///
+/// Every sample is added to all the pixels whose center is within `radius`
+/// of it on both axes, weighted by the filter at the offset from the sample
+/// to the pixel center. The filters are separable, the weight is the product
+/// of the filter on each axis. The radius must be at least 0.5, smaller
+/// filters would leave gaps between the pixels that no sample reaches.
+///
+/// * `Box` - Same weight for every sample within the radius. With a radius of
+///   0.5 each sample only counts for the pixel it was taken in.
+/// * `Tent` - Weight falling linearly from the center to zero at the radius.
+/// * `Gaussian` - Gaussian of standard deviation `sigma`, shifted down so it
+///   reaches zero at the radius.
+/// * `Mitchell` - Mitchell-Netravali cubic with parameters `b` and `c`, a
+///   third both is the usual choice. Its negative lobes sharpen the image but
+///   can cause ringing around high contrast edges.
+/// * `BlackmanHarris` - Blackman-Harris window, close to a Gaussian but
+///   smoothly falling to zero at the radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    Box { radius: f64 },
+    Tent { radius: f64 },
+    Gaussian { radius: f64, sigma: f64 },
+    Mitchell { radius: f64, b: f64, c: f64 },
+    BlackmanHarris { radius: f64 },
+}
+
+impl Filter {
+    /// Distance from the center, on each axis, beyond which the filter is
+    /// zero.
+    pub fn radius(&self) -> f64 {
+        match *self {
+            Filter::Box { radius }
+            | Filter::Tent { radius }
+            | Filter::Gaussian { radius, .. }
+            | Filter::Mitchell { radius, .. }
+            | Filter::BlackmanHarris { radius } => radius,
+        }
+    }
+
+    /// Weight of a sample `x`, `y` pixels away from a pixel center.
+    pub fn evaluate(&self, x: f64, y: f64) -> f64 {
+        self.evaluate_1d(x) * self.evaluate_1d(y)
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let x = x.abs();
+
+        if x > self.radius() {
+            return 0.0;
+        }
+
+        match *self {
+            Filter::Box { .. } => 1.0,
+            Filter::Tent { radius } => 1.0 - x / radius,
+            Filter::Gaussian { radius, sigma } => {
+                let gaussian = |x: f64| (-x * x / (2.0 * sigma * sigma)).exp();
+
+                (gaussian(x) - gaussian(radius)).max(0.0)
+            }
+            Filter::Mitchell { radius, b, c } => mitchell(2.0 * x / radius, b, c),
+            Filter::BlackmanHarris { radius } => {
+                let t = 0.5 + 0.5 * x / radius;
+
+                0.35875 - 0.48829 * (2.0 * PI * t).cos() + 0.14128 * (4.0 * PI * t).cos()
+                    - 0.01168 * (6.0 * PI * t).cos()
+            }
+        }
+    }
+}
+
+/// Mitchell-Netravali cubic, `x` going from 0 to 2.
+fn mitchell(x: f64, b: f64, c: f64) -> f64 {
+    let x2 = x * x;
+    let x3 = x2 * x;
+
+    if x > 1.0 {
+        ((-b - 6.0 * c) * x3
+            + (6.0 * b + 30.0 * c) * x2
+            + (-12.0 * b - 48.0 * c) * x
+            + (8.0 * b + 24.0 * c))
+            / 6.0
+    } else {
+        ((12.0 - 9.0 * b - 6.0 * c) * x3 + (-18.0 + 12.0 * b + 6.0 * c) * x2 + (6.0 - 2.0 * b))
+            / 6.0
+    }
+}
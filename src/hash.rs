@@ -0,0 +1,45 @@
+//! Hashing helpers.
+
+use std::hash::Hasher;
+
+/// 64 bit FNV-1a hasher.
+///
+/// Unlike `DefaultHasher` its output is stable across platforms and Rust
+/// versions, which matters for hashes written to disk.
+#[derive(Clone, Copy, Debug)]
+pub struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// Hash of the `Debug` representation of a value.
+pub fn debug_hash<T: std::fmt::Debug + ?Sized>(value: &T) -> u64 {
+    let mut hasher = Fnv1a::default();
+    hasher.write(format!("{:?}", value).as_bytes());
+    hasher.finish()
+}
+
+/// Scrambles the bits of a hash so every bit of the input affects all of the
+/// output, using the finalizer of splitmix64.
+pub fn finalize(hash: u64) -> u64 {
+    let mut z = hash.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
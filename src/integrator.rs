@@ -0,0 +1,519 @@
+//! Integrators, the ways of computing the color a camera ray sees.
+
+use crate::aov::{self, FirstHit, PathSample};
+use crate::bvh;
+use crate::intersectable::{Intersectable, Intersection};
+use crate::material::{Lobe, Medium, Scatterable};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::spectrum::Wavelengths;
+use crate::Color;
+use crate::Scene;
+use crate::Vec3;
+
+/// Scattering events a ray can go through inside a medium before the path is
+/// given up, between two surfaces.
+const MAX_WALK_STEPS: u32 = 256;
+
+/// How the color of the camera rays is computed.
+///
+/// Besides the path tracer there are integrators meant to debug scenes. They
+/// produce colors in [0, 1] that are still gamma corrected, set the gamma to
+/// 1.0 to see them as they are.
+///
+/// * `PathTracer` - Global illumination, the rendered image.
+/// * `Spectral` - Global illumination following light by wavelength instead
+///   of by color, for materials whose scattering depends on it like
+///   dispersive glass, see `RefractiveIndex`. Colors are turned into smooth
+///   spectra along the path and the light found into a color at the end,
+///   the noise showing up as color noise.
+/// * `Normals` - Shading normal of the first hit, each axis mapped from
+///   [-1, 1] to [0, 1].
+/// * `Depth` - Distance to the first hit, from white right at the camera to
+///   black at `max_distance` and beyond.
+/// * `Uv` - Texture coordinates of the first hit in the red and green
+///   channels.
+/// * `MaterialId` - A different color for every material, objects with equal
+///   materials share it.
+/// * `AmbientOcclusion` - How much of the hemisphere over the first hit is
+///   unoccluded within `distance`.
+/// * `BvhHeatmap` - Bounding boxes and primitives the camera ray was tested
+///   against before finding the first hit, from blue for none to red for
+///   `max_tests` or more.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Integrator {
+    PathTracer,
+    Spectral,
+    Normals,
+    Depth { max_distance: f64 },
+    Uv,
+    MaterialId,
+    AmbientOcclusion { distance: f64 },
+    BvhHeatmap { max_tests: u32 },
+}
+
+impl Integrator {
+    /// Computes what a camera ray sees. The debug integrators report their
+    /// color as the emission of the first hit.
+    pub fn trace(
+        &self,
+        scene: &Scene,
+        ray: &Ray,
+        max_depth: u32,
+        sampler: &mut Sampler,
+    ) -> PathSample {
+        match self {
+            Integrator::PathTracer => return path_trace(scene, ray, max_depth, None, sampler),
+            Integrator::Spectral => {
+                let wavelengths = Wavelengths::sample(sampler.get_1d());
+
+                return path_trace(scene, ray, max_depth, Some(wavelengths), sampler);
+            }
+            _ => {}
+        }
+
+        let tests_before = bvh::intersection_tests();
+        let intersection = scene.world.intersect(ray, 0.0001, f64::INFINITY);
+        let tests = bvh::intersection_tests() - tests_before;
+
+        let mut intersection = match (self, intersection) {
+            (Integrator::BvhHeatmap { max_tests }, _) => {
+                let heat = (tests as f64 / f64::from(*max_tests)).min(1.0);
+
+                return PathSample {
+                    emission: heatmap(heat),
+                    ..PathSample::default()
+                };
+            }
+            (_, Some(intersection)) => intersection,
+            (_, None) => return PathSample::default(),
+        };
+
+        intersection.compute_differentials(ray);
+
+        let normal = intersection.normal;
+        let distance = intersection.t * ray.direction.length();
+
+        let color = match *self {
+            Integrator::Normals => Color::new(
+                normal.x * 0.5 + 0.5,
+                normal.y * 0.5 + 0.5,
+                normal.z * 0.5 + 0.5,
+            ),
+            Integrator::Depth { max_distance } => {
+                let gray = (1.0 - distance / max_distance).max(0.0);
+
+                Color::new(gray, gray, gray)
+            }
+            Integrator::Uv => Color::new(intersection.u, intersection.v, 0.0),
+            Integrator::MaterialId => aov::id_color(intersection.material_id),
+            Integrator::AmbientOcclusion { distance } => {
+                ambient_occlusion(scene, ray, &intersection, distance, sampler)
+            }
+            Integrator::PathTracer | Integrator::Spectral | Integrator::BvhHeatmap { .. } => {
+                Color::default()
+            }
+        };
+
+        PathSample {
+            hit: Some(first_hit(ray, &intersection, Color::default())),
+            emission: color,
+            ..PathSample::default()
+        }
+    }
+}
+
+/// Follows a camera ray around the scene bouncing off the surfaces it hits.
+///
+/// Lights are found both by the bounces and by sampling a point on one of
+/// them at every hit, the two estimates combined with multiple importance
+/// sampling (power heuristic). Light traveling inside a medium, see
+/// `Scatterable::medium`, is attenuated by the distance and follows a random
+/// walk through it if the medium scatters light.
+///
+/// With `wavelengths` the colors along the path hold the light at each of
+/// them instead, turned back into colors once the path ends. Past a
+/// dispersive surface only the hero wavelength is followed.
+fn path_trace(
+    scene: &Scene,
+    ray: &Ray,
+    max_depth: u32,
+    wavelengths: Option<Wavelengths>,
+    sampler: &mut Sampler,
+) -> PathSample {
+    let spectrum = |color: Color| wavelengths.map_or(color, |w| w.upsample(color));
+    let wavelength = wavelengths.map(|w| w.hero());
+    let mut path = PathSample::default();
+    let mut ray = Ray {
+        wavelength,
+        ..ray.clone()
+    };
+    let mut throughput = Color::new(1.0, 1.0, 1.0);
+    let mut lobe = None;
+    // Density the previous bounce picked the ray with, zero for directions
+    // that can only be sampled or for camera rays.
+    let mut bounce_pdf = 0.0;
+    // Medium the ray travels through.
+    let mut medium: Option<Medium> = None;
+    let mut hero_only = false;
+
+    'path: for depth in 1.. {
+        let mut walk_steps = 0;
+
+        let mut intersection = loop {
+            let intersection = match scene.world.intersect(&ray, 0.0001, f64::INFINITY) {
+                Some(intersection) => intersection,
+                None => break 'path,
+            };
+
+            let medium = match medium {
+                Some(medium) => medium,
+                None => break intersection,
+            };
+
+            let length = ray.direction.length();
+            let distance = intersection.t * length;
+            let extinction = medium.absorption + medium.scattering;
+
+            if is_black(medium.scattering) {
+                throughput = throughput * extinction.transmittance(distance);
+
+                break intersection;
+            }
+
+            // Distances are picked for one of the colors at a time, more
+            // often for the ones carrying more light, and weighted by the
+            // density of picking them for any of them.
+            let total = throughput.r + throughput.g + throughput.b;
+
+            if total <= 0.0 {
+                break 'path;
+            }
+
+            let chances = throughput / total;
+            let u = sampler.get_1d();
+            let channel = if u < chances.r {
+                extinction.r
+            } else if u < chances.r + chances.g {
+                extinction.g
+            } else {
+                extinction.b
+            };
+            let traveled = -(1.0 - sampler.get_1d()).ln() / channel;
+
+            if traveled >= distance {
+                let transmittance = extinction.transmittance(distance);
+
+                throughput = throughput * transmittance / dot(chances, transmittance);
+
+                break intersection;
+            }
+
+            walk_steps += 1;
+
+            if walk_steps > MAX_WALK_STEPS {
+                break 'path;
+            }
+
+            let transmittance = extinction.transmittance(traveled);
+
+            throughput = throughput * medium.scattering * transmittance
+                / dot(chances, extinction * transmittance);
+
+            let direction = henyey_greenstein(ray.direction / length, medium.anisotropy, sampler);
+
+            ray = Ray {
+                wavelength,
+                ..Ray::new(ray.point_at(traveled / length), direction)
+            };
+            bounce_pdf = 0.0;
+        };
+
+        intersection.compute_differentials(&ray);
+
+        let emitted = spectrum(intersection.material.emit(&ray, &intersection));
+        let weight = if bounce_pdf > 0.0 {
+            power_heuristic(bounce_pdf, scene.world.light_pdf(ray.origin, &intersection))
+        } else {
+            1.0
+        };
+
+        path.add_light(throughput * emitted * weight, depth, lobe);
+
+        let scattered = intersection.material.sample(&ray, &intersection, sampler);
+
+        if depth < max_depth && scene.world.has_lights() {
+            let light = sample_light(scene, &ray, &intersection, medium, wavelengths, sampler);
+            let lobe = lobe
+                .or(scattered.as_ref().map(|scattered| scattered.lobe))
+                .or(Some(Lobe::Diffuse));
+
+            path.add_light(throughput * light, depth + 1, lobe);
+        }
+
+        if depth == 1 {
+            let albedo = scattered
+                .as_ref()
+                .map_or(Color::default(), |scattered| scattered.attenuation);
+
+            path.hit = Some(first_hit(&ray, &intersection, albedo));
+        }
+
+        let scattered = match scattered {
+            Some(scattered) => scattered,
+            None => break,
+        };
+
+        if depth >= max_depth {
+            break;
+        }
+
+        throughput = throughput * spectrum(scattered.attenuation);
+
+        if wavelengths.is_some() && !hero_only && intersection.material.is_dispersive() {
+            // The other wavelengths would have gone elsewhere, the hero
+            // carries the estimate of the three from here on.
+            throughput = Color::new(throughput.r * 3.0, 0.0, 0.0);
+            hero_only = true;
+        }
+
+        // Paths that can't carry much light anymore are likely to stop, the
+        // ones that go on make up for the others.
+        if depth > 5 {
+            let p = throughput.r.max(throughput.g).max(throughput.b).min(1.0);
+
+            if sampler.get_1d() < p {
+                throughput = throughput / p;
+            } else {
+                break;
+            }
+        }
+
+        lobe = lobe.or(Some(scattered.lobe));
+        bounce_pdf = scattered.pdf;
+
+        medium = medium_towards(
+            &intersection,
+            scattered.scattered.direction,
+            medium,
+            wavelengths,
+        );
+
+        ray = Ray {
+            wavelength,
+            ..scattered.scattered
+        };
+    }
+
+    if let Some(wavelengths) = wavelengths {
+        path.emission = wavelengths.color(path.emission);
+        path.diffuse_direct = wavelengths.color(path.diffuse_direct);
+        path.diffuse_indirect = wavelengths.color(path.diffuse_indirect);
+        path.specular_direct = wavelengths.color(path.specular_direct);
+        path.specular_indirect = wavelengths.color(path.specular_indirect);
+    }
+
+    path
+}
+
+/// Light reaching `intersection` straight from a point picked on a light,
+/// scattered back along `ray` and weighted for multiple importance sampling.
+/// `medium` is the medium around the intersection, whose absorption and
+/// scattering take light away on the way. The light is given at each of the
+/// `wavelengths` if any.
+fn sample_light(
+    scene: &Scene,
+    ray: &Ray,
+    intersection: &Intersection,
+    medium: Option<Medium>,
+    wavelengths: Option<Wavelengths>,
+    sampler: &mut Sampler,
+) -> Color {
+    let spectrum = |color: Color| wavelengths.map_or(color, |w| w.upsample(color));
+
+    let sample = match scene.world.sample_light(sampler) {
+        Some(sample) => sample,
+        None => return Color::default(),
+    };
+
+    let to_light = sample.point - intersection.p;
+    let distance = to_light.length();
+    let direction = to_light / distance;
+
+    if !(distance > 0.0 && direction.is_finite()) {
+        return Color::default();
+    }
+
+    let material = intersection.material;
+    let reflected = material.evaluate(ray, intersection, direction);
+
+    if reflected.r <= 0.0 && reflected.g <= 0.0 && reflected.b <= 0.0 {
+        return Color::default();
+    }
+
+    // The point is only lit if the first thing in its direction is the point
+    // itself.
+    let shadow = Ray::new(intersection.p, direction);
+    let tolerance = 1e-6 * (1.0 + distance);
+
+    let hit = match scene
+        .world
+        .intersect(&shadow, 0.0001, distance + tolerance * 2.0)
+    {
+        Some(hit) if hit.object_id == sample.object_id && hit.t > distance - tolerance => hit,
+        _ => return Color::default(),
+    };
+
+    let light_pdf = scene.world.light_pdf(intersection.p, &hit);
+
+    if light_pdf <= 0.0 {
+        return Color::default();
+    }
+
+    let weight = power_heuristic(light_pdf, material.pdf(ray, intersection, direction));
+
+    let medium = medium_towards(intersection, direction, medium, wavelengths);
+    let transmittance = medium.map_or(Color::new(1.0, 1.0, 1.0), |medium| {
+        (medium.absorption + medium.scattering).transmittance(distance)
+    });
+    let emitted = spectrum(hit.material.emit(&shadow, &hit));
+
+    emitted * spectrum(reflected) * transmittance * (weight / light_pdf)
+}
+
+/// Medium a ray leaving `intersection` in `direction` travels through, the
+/// one enclosed by the surface if it goes in, none if it goes out or
+/// `current` if the surface doesn't enclose a medium. Given at each of the
+/// `wavelengths` if any.
+fn medium_towards(
+    intersection: &Intersection,
+    direction: Vec3,
+    current: Option<Medium>,
+    wavelengths: Option<Wavelengths>,
+) -> Option<Medium> {
+    let spectrum = |color: Color| wavelengths.map_or(color, |w| w.upsample(color));
+
+    match intersection.material.medium(intersection) {
+        Some(medium) if direction.dot(intersection.normal) < 0.0 => Some(Medium {
+            absorption: spectrum(medium.absorption),
+            scattering: spectrum(medium.scattering),
+            ..medium
+        }),
+        Some(_) => None,
+        None => current,
+    }
+}
+
+/// Direction light going along `direction`, normalized, is scattered to by
+/// a medium with the given anisotropy, following the Henyey-Greenstein phase
+/// function.
+fn henyey_greenstein(direction: Vec3, anisotropy: f64, sampler: &mut Sampler) -> Vec3 {
+    let g = anisotropy;
+    let (u, v) = sampler.get_2d();
+
+    let cos_theta = if g.abs() < 1e-3 {
+        1.0 - 2.0 * u
+    } else {
+        let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * u);
+
+        ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+    };
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * std::f64::consts::PI * v;
+
+    // Orthonormal basis around the direction.
+    let helper = if direction.x.abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let tangent = direction.cross(helper).normalize();
+    let bitangent = direction.cross(tangent);
+
+    tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + direction * cos_theta
+}
+
+fn is_black(color: Color) -> bool {
+    color.r <= 0.0 && color.g <= 0.0 && color.b <= 0.0
+}
+
+fn dot(a: Color, b: Color) -> f64 {
+    a.r * b.r + a.g * b.g + a.b * b.b
+}
+
+/// Weight of a sample picked with density `pdf` by one of two techniques,
+/// the other one having picked it with density `other`.
+fn power_heuristic(pdf: f64, other: f64) -> f64 {
+    let pdf = pdf * pdf;
+    let other = other * other;
+
+    if pdf + other > 0.0 {
+        pdf / (pdf + other)
+    } else {
+        0.0
+    }
+}
+
+/// First hit record of a camera ray.
+fn first_hit(ray: &Ray, intersection: &Intersection, albedo: Color) -> FirstHit {
+    FirstHit {
+        distance: intersection.t * ray.direction.length(),
+        position: intersection.p,
+        normal: intersection.normal,
+        u: intersection.u,
+        v: intersection.v,
+        albedo,
+        object_id: intersection.object_id,
+        material_id: intersection.material_id,
+    }
+}
+
+/// White if a ray leaving the hit point in a cosine distributed direction
+/// over the normal hits nothing within `distance`, black otherwise.
+fn ambient_occlusion(
+    scene: &Scene,
+    ray: &Ray,
+    intersection: &Intersection,
+    distance: f64,
+    sampler: &mut Sampler,
+) -> Color {
+    // Look at the side of the surface the camera ray came from.
+    let normal = if intersection.normal.dot(ray.direction) > 0.0 {
+        -intersection.normal
+    } else {
+        intersection.normal
+    };
+
+    // Orthonormal basis around the normal.
+    let helper = if normal.x.abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let tangent = normal.cross(helper).normalize();
+    let bitangent = normal.cross(tangent);
+
+    let (u, v) = sampler.get_2d();
+    let phi = 2.0 * std::f64::consts::PI * u;
+    let r = v.sqrt();
+    let direction =
+        tangent * (r * phi.cos()) + bitangent * (r * phi.sin()) + normal * (1.0 - v).sqrt();
+
+    let occluded = scene
+        .world
+        .intersect(&Ray::new(intersection.p, direction), 0.0001, distance)
+        .is_some();
+
+    if occluded {
+        Color::new(0.0, 0.0, 0.0)
+    } else {
+        Color::new(1.0, 1.0, 1.0)
+    }
+}
+
+/// Color ramp from blue at 0.0 through green to red at 1.0.
+fn heatmap(heat: f64) -> Color {
+    let channel = |center: f64| (1.5 - (4.0 * heat - center).abs()).clamp(0.0, 1.0);
+
+    Color::new(channel(3.0), channel(2.0), channel(1.0))
+}
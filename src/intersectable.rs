@@ -1,5 +1,6 @@
 use crate::aabb::AABB;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 use crate::Material;
 use crate::Vec3;
 
@@ -9,9 +10,46 @@ use std::marker::{Send, Sync};
 /// Hitable is a boxed trait object that implements `Intersectable`.
 pub type Hitable = Box<dyn Intersectable + Send + Sync>;
 
+/// A shape rays can hit.
+///
+/// Implement it to add a shape to the path tracer, `shape::validate` checks
+/// that an implementation keeps the invariants below.
 pub trait Intersectable: Debug + Send + Sync {
-    fn intersect(&self, ray: &Ray, min: f64, max: f64) -> Option<Intersection>;
+    /// Nearest hit of `ray` with the shape whose distance `t` is strictly
+    /// between `min` and `max`, `None` if there is none.
+    ///
+    /// The distance is measured in multiples of `ray.direction`, which isn't
+    /// necessarily normalized, and the hit point `p` must be
+    /// `ray.point_at(t)`. The normal must be normalized and point out of the
+    /// shape no matter the side the ray came from, materials like glass look at
+    /// which side of the surface they are hit from. The texture coordinates
+    /// and their derivatives must be finite, the differentials, `object_id`
+    /// and `material_id` are left at zero for the renderer to fill in, see
+    /// `Intersection::new`.
+    fn intersect(&self, ray: &Ray, min: f64, max: f64) -> Option<Intersection<'_>>;
+
+    /// Box enclosing every point `intersect` can return. It must be finite,
+    /// `BVH::from_vec` refuses shapes with infinite or NaN bounding boxes.
     fn bounding_box(&self) -> AABB;
+
+    /// Material of the whole shape, `None` for shapes with several of them
+    /// like a `BVH`. Shapes with an emissive material are sampled as lights.
+    fn material(&self) -> Option<&Material> {
+        None
+    }
+
+    /// Surface area of the shape, zero for shapes that don't support
+    /// `sample_surface`.
+    fn area(&self) -> f64 {
+        0.0
+    }
+
+    /// Point picked uniformly over the surface of the shape, one `intersect`
+    /// could return. Used to sample lights, shapes returning `None` are only
+    /// lit by rays that happen to hit them.
+    fn sample_surface(&self, _sampler: &mut Sampler) -> Option<Vec3> {
+        None
+    }
 }
 
 /// Intersection record. When we hit an object, this is where we store that hit.
@@ -22,7 +60,18 @@ pub trait Intersectable: Debug + Send + Sync {
 /// * `normal` - Normal from the hit point.
 /// * `u` - Texture coordinates.
 /// * `v` - Texture coordinates.
+/// * `dpdu` - Partial derivative of the hit point with respect to `u`.
+/// * `dpdv` - Partial derivative of the hit point with respect to `v`.
+/// * `dudx`, `dvdx`, `dudy`, `dvdy` - Change of the texture coordinates from
+///   one pixel to the next in screen x and y. Zero unless the ray carried
+///   differentials, see `compute_differentials`.
 /// * `material` - Material of the hit object.
+/// * `object_id` - Index of the hit object in the list the scene's `BVH` was
+///   built from. Shapes leave it at zero, the `BVH` sets it.
+/// * `material_id` - Hash of the material of the hit object, the same for
+///   every object with an equal material. Shapes leave it at zero, the `BVH`
+///   sets it for objects with a single material, see
+///   `Intersectable::material`.
 #[derive(Clone, Debug)]
 pub struct Intersection<'a> {
     pub p: Vec3,
@@ -30,5 +79,136 @@ pub struct Intersection<'a> {
     pub normal: Vec3,
     pub u: f64,
     pub v: f64,
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
+    pub dudx: f64,
+    pub dvdx: f64,
+    pub dudy: f64,
+    pub dvdy: f64,
     pub material: &'a Material,
+    pub object_id: u32,
+    pub material_id: u64,
+}
+
+impl<'a> Intersection<'a> {
+    /// Creates an intersection at distance `t` along the ray, at point `p`,
+    /// with texture coordinates `u` and `v`.
+    ///
+    /// `dpdu` and `dpdv` are set to unit vectors perpendicular to `normal`,
+    /// shapes that know the actual derivatives can set them afterwards. The
+    /// differentials, `object_id` and `material_id` are left at zero.
+    pub fn new(t: f64, p: Vec3, normal: Vec3, u: f64, v: f64, material: &'a Material) -> Self {
+        let (dpdu, dpdv) = tangent_vectors(normal);
+
+        Intersection {
+            p,
+            t,
+            normal,
+            u,
+            v,
+            dpdu,
+            dpdv,
+            dudx: 0.0,
+            dvdx: 0.0,
+            dudy: 0.0,
+            dvdy: 0.0,
+            material,
+            object_id: 0,
+            material_id: 0,
+        }
+    }
+
+    /// Estimates the texture space footprint of the ray that produced this
+    /// intersection by intersecting its differential rays with the tangent
+    /// plane at the hit point.
+    pub fn compute_differentials(&mut self, ray: &Ray) {
+        let differential = match &ray.differential {
+            Some(differential) => differential,
+            None => return,
+        };
+
+        let n = self.normal;
+        let d = n.dot(self.p);
+
+        let tx = (d - n.dot(differential.rx_origin)) / n.dot(differential.rx_direction);
+        let ty = (d - n.dot(differential.ry_origin)) / n.dot(differential.ry_direction);
+
+        if !tx.is_finite() || !ty.is_finite() {
+            return;
+        }
+
+        let dpdx = differential.rx_origin + tx * differential.rx_direction - self.p;
+        let dpdy = differential.ry_origin + ty * differential.ry_direction - self.p;
+
+        // Solve the overdetermined system dp = dpdu * du + dpdv * dv using the two
+        // coordinates where the normal is smallest.
+        let (a, b) = if n.x.abs() > n.y.abs() && n.x.abs() > n.z.abs() {
+            (1, 2)
+        } else if n.y.abs() > n.z.abs() {
+            (0, 2)
+        } else {
+            (0, 1)
+        };
+
+        let m = [
+            [axis(self.dpdu, a), axis(self.dpdv, a)],
+            [axis(self.dpdu, b), axis(self.dpdv, b)],
+        ];
+
+        let (dudx, dvdx) = solve_linear_system_2x2(m, [axis(dpdx, a), axis(dpdx, b)]);
+        let (dudy, dvdy) = solve_linear_system_2x2(m, [axis(dpdy, a), axis(dpdy, b)]);
+
+        self.dudx = dudx;
+        self.dvdx = dvdx;
+        self.dudy = dudy;
+        self.dvdy = dvdy;
+    }
+
+    /// Width in texture space of the area covered by this intersection.
+    pub fn uv_width(&self) -> f64 {
+        let x = (self.dudx * self.dudx + self.dvdx * self.dvdx).sqrt();
+        let y = (self.dudy * self.dudy + self.dvdy * self.dvdy).sqrt();
+
+        x.max(y)
+    }
+}
+
+fn axis(v: Vec3, axis: usize) -> f64 {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+fn solve_linear_system_2x2(m: [[f64; 2]; 2], b: [f64; 2]) -> (f64, f64) {
+    let det = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+
+    if det.abs() < 1e-12 {
+        return (0.0, 0.0);
+    }
+
+    let x0 = (m[1][1] * b[0] - m[0][1] * b[1]) / det;
+    let x1 = (m[0][0] * b[1] - m[1][0] * b[0]) / det;
+
+    if x0.is_finite() && x1.is_finite() {
+        (x0, x1)
+    } else {
+        (0.0, 0.0)
+    }
+}
+
+/// Two unit vectors perpendicular to `normal` and to each other.
+pub(crate) fn tangent_vectors(normal: Vec3) -> (Vec3, Vec3) {
+    let normal = normal.normalize();
+    let helper = if normal.x.abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+
+    let tangent = helper.cross(normal).normalize();
+    let bitangent = normal.cross(tangent);
+
+    (tangent, bitangent)
 }
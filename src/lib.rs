@@ -1,35 +1,60 @@
 #![allow(dead_code)]
 
 mod aabb;
+mod aov;
 mod bvh;
 mod camera;
+mod checkpoint;
 mod color;
+mod denoise;
+mod error;
+mod film;
+mod filter;
+mod hash;
+mod integrator;
 mod intersectable;
 mod material;
 mod ray;
 mod rng;
+mod sampler;
 mod scene;
+mod settings;
+mod spectrum;
 mod texture;
+mod tile;
 mod vector;
 
 pub mod shape;
 
+pub use aabb::AABB;
+pub use aov::Aov;
 pub use bvh::BVH;
 pub use camera::Camera;
 pub use color::Color;
-pub use material::Material;
+pub use error::Error;
+pub use film::Snapshot;
+pub use filter::Filter;
+pub use integrator::Integrator;
+pub use intersectable::{Intersectable, Intersection};
+pub use material::{
+    DiffuseLight, Lobe, Material, Medium, Principled, RefractiveIndex, Scatterable, Scattered,
+};
+pub use ray::{Ray, RayDifferential};
+pub use sampler::{Sampler, SamplerKind};
 pub use scene::Scene;
-pub use texture::Texture;
+pub use settings::{AdaptiveSampling, Checkpoint, Denoise, Progressive, RenderSettings};
+pub use texture::{ColorChannel, Texture, TextureCache, TextureObject};
+pub use tile::{Tile, TileOrder};
 pub use vector::Vec3;
 
-use intersectable::*;
-use ray::Ray;
+use film::{Film, FilmTile};
 
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
 use std::time::{Duration, Instant};
 
+use image::Rgb32FImage;
 use indicatif::{ProgressBar, ProgressStyle};
 use threadpool::ThreadPool;
 
@@ -41,158 +66,604 @@ pub type Hitable = Box<dyn Intersectable + Send + Sync>;
 /// # Arguments
 ///
 /// * `scene` - Scene to render
-/// * `width` - Width of the resulting image.
-/// * `height` - Height of the resulting image.
-/// * `samples` - Samples per pixel to take.
-/// * `max_depth` - Hard limit of ray bouncing for the scene.
-/// * `gamma` - Gamma value used for gamma correction of the final image.
-/// * `workers` - How many threads to use.
+/// * `settings` - Image size, samples and the rest of the render parameters.
 /// * `image` - Filename of the saved image.
 ///
+/// # Errors
+/// Fails if any of the settings is out of range, if a worker panics or if the
+/// image or a checkpoint can't be saved.
+///
 /// # Remarks
-/// The path tracer does subpixel sampling (4 samples) using a tent distribution
-/// so it traces `4 * samples` rays per pixel. It uses a russian roulette
-/// implementation to optimize how many rays are required to render a given
-/// pixel.
-pub fn render(
+/// Every sample traces a ray through a point of the pixel picked by
+/// `settings.sampler`, and its color is added to the pixels around it
+/// weighted by `settings.filter`. It uses a russian roulette implementation
+/// to optimize how many rays are required to render a given pixel.
+///
+/// With adaptive sampling the image is rendered in passes and pixels stop
+/// taking samples once their estimated error is low enough, see
+/// `AdaptiveSampling`. In progressive mode the image is saved after every
+/// pass, see `Progressive`. The render state can be saved along the way and
+/// continued with `resume`, see `Checkpoint`.
+///
+/// The AOVs in `settings.aovs` are saved once the render is done as EXR
+/// images next to the image, named after it and the AOV, e.g. the depth of
+/// `cornell.png` goes to `cornell.depth.exr`. When denoising, the image is
+/// denoised before it's saved and the albedo and normal used to guide the
+/// denoiser are saved as well.
+pub fn render(scene: Scene, settings: &RenderSettings, filename: &str) -> Result<(), Error> {
+    let film = Film::new(settings.width, settings.height, &settings.film_aovs())?;
+
+    render_to_file(scene, settings, film, 0, filename)
+}
+
+/// Continues a render from a checkpoint saved by a previous `render` or
+/// `resume` with the same scene, taking samples until `settings.samples` is
+/// reached.
+///
+/// Every sample gets its random numbers from `settings.seed` and its position
+/// in the image, so resuming with the settings the checkpoint was made with
+/// produces the same image as a render that was never interrupted.
+///
+/// # Arguments
+///
+/// * `scene` - Scene to render, the same the checkpoint was made with.
+/// * `settings` - Render parameters, the image size, maximum depth, AOVs,
+///   integrator, filter, sampler and seed must match the checkpoint.
+/// * `checkpoint` - Filename of the checkpoint to resume.
+/// * `image` - Filename of the saved image.
+///
+/// # Errors
+/// Fails like `render`, and if the checkpoint can't be read or doesn't belong
+/// to the scene and settings.
+pub fn resume<P: AsRef<Path>>(
     scene: Scene,
-    width: u32,
-    height: u32,
-    samples: u32,
-    max_depth: u32,
-    gamma: f64,
-    workers: usize,
+    settings: &RenderSettings,
+    checkpoint: P,
     filename: &str,
-) {
-    // Shared mutable image buffer
-    let imgbuf = Arc::new(Mutex::new(image::ImageBuffer::new(width, height)));
+) -> Result<(), Error> {
+    settings.validate()?;
+
+    let (header, film) = checkpoint::read(checkpoint.as_ref())?;
+
+    if header.width != settings.width || header.height != settings.height {
+        return Err(Error::InvalidCheckpoint("image dimensions don't match"));
+    }
+
+    if header.max_depth != settings.max_depth {
+        return Err(Error::InvalidCheckpoint("max depth doesn't match"));
+    }
+
+    if header.fingerprint != scene.fingerprint() {
+        return Err(Error::InvalidCheckpoint("scene doesn't match"));
+    }
+
+    if header.aovs != settings.film_aovs() {
+        return Err(Error::InvalidCheckpoint("AOVs don't match"));
+    }
+
+    if header.integrator != settings.integrator {
+        return Err(Error::InvalidCheckpoint("integrator doesn't match"));
+    }
+
+    if header.filter != settings.filter {
+        return Err(Error::InvalidCheckpoint("filter doesn't match"));
+    }
+
+    if header.sampler != settings.sampler || header.seed != settings.seed {
+        return Err(Error::InvalidCheckpoint("sampler or seed doesn't match"));
+    }
+
+    render_to_file(scene, settings, film, header.samples, filename)
+}
+
+fn render_to_file(
+    scene: Scene,
+    settings: &RenderSettings,
+    film: Film,
+    taken: u32,
+    filename: &str,
+) -> Result<(), Error> {
+    let progressive = settings.progressive.is_some();
+
+    let film = render_film(scene, settings, film, taken, |film, _samples, _elapsed| {
+        if progressive {
+            save_image(&film_image(film, settings), filename)
+        } else {
+            Ok(())
+        }
+    })?;
+
+    if !progressive {
+        save_image(&film_image(&film, settings), filename)?;
+    }
+
+    if let Some(path) = settings
+        .adaptive
+        .as_ref()
+        .and_then(|adaptive| adaptive.sample_map.as_ref())
+    {
+        save_image(&film.sample_map(), path)?;
+    }
+
+    save_aovs(&film, filename)
+}
+
+/// Path tracer renderer handing a snapshot of the image to `on_pass` after
+/// every pass instead of saving it.
+///
+/// Every render is done in a single pass unless `settings` enables
+/// progressive or adaptive sampling. The last snapshot is the final image.
+///
+/// # Errors
+/// Fails if any of the settings is out of range, if a worker panics or if a
+/// checkpoint can't be saved.
+pub fn render_progressive<F>(
+    scene: Scene,
+    settings: &RenderSettings,
+    mut on_pass: F,
+) -> Result<(), Error>
+where
+    F: FnMut(Snapshot),
+{
+    let film = Film::new(settings.width, settings.height, &settings.film_aovs())?;
+
+    render_film(scene, settings, film, 0, |film, samples, elapsed| {
+        on_pass(Snapshot {
+            image: film_image(film, settings),
+            samples,
+            elapsed,
+        });
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// Renders the scene saving the accumulated samples of every pixel to
+/// `partial` instead of an image.
+///
+/// Partial renders of the same scene and settings can be combined with
+/// `merge`, so an image can be split between several machines either by
+/// giving each one a different `settings.region`, or by having all of them
+/// render the whole image with a different `settings.seed` and averaging
+/// their samples. A partial render is
+/// also a checkpoint that can be continued with `resume`.
+///
+/// # Errors
+/// Fails if any of the settings is out of range, if a worker panics or if the
+/// partial render can't be saved.
+pub fn render_partial<P: AsRef<Path>>(
+    scene: Scene,
+    settings: &RenderSettings,
+    partial: P,
+) -> Result<(), Error> {
+    let fingerprint = scene.fingerprint();
+    let film = Film::new(settings.width, settings.height, &settings.film_aovs())?;
+
+    let film = render_film(scene, settings, film, 0, |_film, _samples, _elapsed| Ok(()))?;
+
+    let header = checkpoint::Header::new(settings, fingerprint, film.max_samples());
+
+    checkpoint::write(partial.as_ref(), &header, &film)
+}
+
+/// Combines partial renders saved by `render_partial` into an image.
+///
+/// # Arguments
+///
+/// * `partials` - Filenames of the partial renders.
+/// * `gamma` - Gamma value used for gamma correction of the final image.
+/// * `image` - Filename of the saved image. The AOVs of the partial renders
+///   are saved next to it like `render` does.
+///
+/// # Errors
+/// Fails if there are no partial renders, if they can't be read or if they
+/// don't belong to the same scene, image size, maximum depth, AOVs,
+/// integrator, filter and sampler. Partial renders of regions must cover the
+/// whole image between them, and those with the same seed must not overlap.
+pub fn merge<P: AsRef<Path>>(partials: &[P], gamma: f64, filename: &str) -> Result<(), Error> {
+    let (header, film) = merge_partials(partials)?;
+
+    println!(
+        "Merged {} partial renders of a {}x{} image.",
+        partials.len(),
+        header.width,
+        header.height
+    );
+
+    save_image(&film.to_image(gamma.recip()), filename)?;
+    save_aovs(&film, filename)
+}
+
+/// Denoises a rendered image guided by the albedo and normal of its first
+/// hits, the way `render` does when `settings.denoise` is set.
+///
+/// # Arguments
+///
+/// * `color` - Rendered image before gamma correction, like the `Beauty` AOV.
+/// * `albedo` - The `Albedo` AOV of the render.
+/// * `normal` - The `Normal` AOV of the render.
+/// * `settings` - Denoiser parameters.
+///
+/// # Errors
+/// Fails if any of the settings is out of range or if the images have
+/// different dimensions.
+pub fn denoise(
+    color: &Rgb32FImage,
+    albedo: &Rgb32FImage,
+    normal: &Rgb32FImage,
+    settings: &Denoise,
+) -> Result<Rgb32FImage, Error> {
+    settings.validate()?;
+
+    if albedo.dimensions() != color.dimensions() || normal.dimensions() != color.dimensions() {
+        return Err(Error::MismatchedImages);
+    }
+
+    Ok(denoise::a_trous(color, albedo, normal, settings))
+}
+
+fn merge_partials<P: AsRef<Path>>(partials: &[P]) -> Result<(checkpoint::Header, Film), Error> {
+    let (first, rest) = partials
+        .split_first()
+        .ok_or(Error::MismatchedPartials("there are no partial renders"))?;
+
+    let (header, mut film) = checkpoint::read(first.as_ref())?;
+
+    // Partial renders with the same seed take the same samples, where their
+    // regions overlap they would be counted twice.
+    let mut sampled: HashMap<u64, Vec<bool>> = HashMap::new();
+
+    sampled.insert(header.seed, film.sampled_pixels().collect());
+
+    for partial in rest {
+        let (other, other_film) = checkpoint::read(partial.as_ref())?;
+
+        if other.width != header.width || other.height != header.height {
+            return Err(Error::MismatchedPartials("image dimensions don't match"));
+        }
+
+        if other.max_depth != header.max_depth {
+            return Err(Error::MismatchedPartials("max depth doesn't match"));
+        }
+
+        if other.fingerprint != header.fingerprint {
+            return Err(Error::MismatchedPartials("scenes don't match"));
+        }
+
+        if other.aovs != header.aovs {
+            return Err(Error::MismatchedPartials("AOVs don't match"));
+        }
+
+        if other.integrator != header.integrator {
+            return Err(Error::MismatchedPartials("integrators don't match"));
+        }
+
+        if other.filter != header.filter {
+            return Err(Error::MismatchedPartials("filters don't match"));
+        }
+
+        if other.sampler != header.sampler {
+            return Err(Error::MismatchedPartials("samplers don't match"));
+        }
+
+        let seen = sampled
+            .entry(other.seed)
+            .or_insert_with(|| vec![false; film.sampled_pixels().count()]);
+
+        for (seen, sampled) in seen.iter_mut().zip(other_film.sampled_pixels()) {
+            if *seen && sampled {
+                return Err(Error::MismatchedPartials(
+                    "partial renders with the same seed overlap",
+                ));
+            }
+
+            *seen |= sampled;
+        }
+
+        film.merge(&other_film);
+    }
+
+    if !film.is_covered() {
+        return Err(Error::MismatchedPartials(
+            "partial renders don't cover the whole image",
+        ));
+    }
+
+    Ok((header, film))
+}
+
+/// Renders the scene in passes adding samples to `film`, which already has
+/// `taken` samples per pixel. Calls `on_pass` with the film, the samples per
+/// pixel taken so far and the time elapsed after every pass.
+fn render_film<F>(
+    scene: Scene,
+    settings: &RenderSettings,
+    mut film: Film,
+    mut taken: u32,
+    mut on_pass: F,
+) -> Result<Film, Error>
+where
+    F: FnMut(&Film, u32, Duration) -> Result<(), Error>,
+{
+    settings.validate()?;
+
+    let width = settings.width;
+    let height = settings.height;
+    let samples = settings.samples;
+    let max_depth = settings.max_depth;
+    let workers = settings.workers;
+    let seed = settings.seed;
+    let sampler_kind = settings.sampler;
+    let filter = settings.filter;
+    let integrator = settings.integrator;
+    let aovs = Arc::new(settings.film_aovs());
+
+    let header = checkpoint::Header::new(settings, scene.fingerprint(), taken);
+
     // Shared scene buffer
     let scene = Arc::new(scene);
     // Progress bar
-    let pb = ProgressBar::new(u64::from(width * height));
+    let region = settings.region();
+    let pb = ProgressBar::new(region.area() as u64 * u64::from(samples));
 
     pb.set_style(ProgressStyle::default_bar().template(
         "{spinner:.green} [{elapsed_precise}] [{bar:40.red/gray}] {percent}/100% ({eta_precise})",
     ).unwrap());
-
-    let gamma_correction = gamma.recip();
+    pb.set_position(film.pixels().iter().map(|p| u64::from(p.samples)).sum());
 
     let w = f64::from(width).recip();
     let h = f64::from(height).recip();
-    let s = (f64::from(samples) * 4.0).recip();
+
+    // Ray differentials span the distance between samples rather than between
+    // pixels, so textures get filtered over the area each sample represents.
+    let differential_scale = f64::from(samples).recip().sqrt().max(0.125);
+    let du = w * differential_scale;
+    let dv = h * differential_scale;
 
     let pool = ThreadPool::new(workers);
 
-    let work_count = Arc::new(AtomicUsize::new(0));
+    let tiles = tile::tiles(region, settings.tile_size, settings.tile_order);
+
+    // Tiles overlap once splatted, they are merged top to bottom and left to
+    // right whatever order they were rendered in so the result doesn't depend
+    // on it.
+    let mut merge_order: Vec<usize> = (0..tiles.len()).collect();
+    merge_order.sort_by_key(|&index| (tiles[index].y, tiles[index].x));
 
     println!("Simple path tracer.");
     println!(
         "Rendering a {}x{}x{}spp image, max depth of {}, using {} workers.",
         width, height, samples, max_depth, workers
     );
+    if taken > 0 {
+        println!("Resuming from {}spp.", taken);
+    }
+    if let Some(adaptive) = &settings.adaptive {
+        println!(
+            "Adaptive sampling from {}spp with a relative error threshold of {}.",
+            adaptive.min_samples, adaptive.threshold
+        );
+    }
+    if let Some(progressive) = &settings.progressive {
+        println!("Progressive passes of {}spp.", progressive.pass_samples);
+    }
     println!();
 
     let start = Instant::now();
+    let mut last_checkpoint = start;
+
+    let mut pass_samples = match (&settings.progressive, &settings.adaptive) {
+        (Some(progressive), _) => progressive.pass_samples,
+        (None, Some(adaptive)) => adaptive.min_samples,
+        (None, None) if settings.checkpoint.is_some() => 1,
+        (None, None) => samples,
+    };
+
+    // Passes only there to save checkpoints between them grow until each one
+    // takes at least a quarter of the checkpoint interval.
+    let pass_interval = match (&settings.progressive, &settings.adaptive) {
+        (None, None) => settings
+            .checkpoint
+            .as_ref()
+            .map(|checkpoint| checkpoint.interval / 4),
+        _ => None,
+    };
+    let time_limit = settings
+        .progressive
+        .as_ref()
+        .and_then(|progressive| progressive.time_limit);
+
+    // Pixels still taking samples, row by row.
+    let mut active: Vec<bool> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| region.contains(x, y)))
+        .collect();
+    let mut traced: u64 = 0;
+
+    if let Some(adaptive) = &settings.adaptive {
+        update_active(&mut active, &film, taken, adaptive);
+    }
+
+    while taken < samples && active.contains(&true) {
+        let pass = samples.min(taken + pass_samples) - taken;
+        let pass_start = Instant::now();
+        let first_sample = taken;
+        let mask = Arc::new(active.clone());
+        let (sender, receiver) = mpsc::channel();
+        let mut jobs = 0;
+
+        for (index, tile) in tiles.iter().copied().enumerate() {
+            if !tile
+                .pixels()
+                .any(|(x, y)| active[y as usize * width as usize + x as usize])
+            {
+                continue;
+            }
 
-    // Iterate over the coordinates and pixels of the image
-    for y in 0..height {
-        for x in 0..width {
-            let img = Arc::clone(&imgbuf);
+            let mask = Arc::clone(&mask);
             let scene = Arc::clone(&scene);
-            let work_count = Arc::clone(&work_count);
+            let aovs = Arc::clone(&aovs);
+            let sender = sender.clone();
+
+            jobs += 1;
 
             pool.execute(move || {
-                let mut pixel_color = Color::new(0.0, 0.0, 0.0);
+                let mut film_tile = FilmTile::new(tile, &filter, width, height, aovs.len());
+                let mut sampler = Sampler::new(sampler_kind, seed, samples);
+                let mut aov_values = Vec::with_capacity(aovs.len());
 
-                for sy in 0..2 {
-                    for sx in 0..2 {
-                        for _i in 0..samples {
-                            let dx = tent_filter_factor();
-                            let dy = tent_filter_factor();
+                for (x, y) in tile.pixels() {
+                    if !mask[y as usize * width as usize + x as usize] {
+                        continue;
+                    }
+
+                    for i in first_sample..first_sample + pass {
+                        rng::seed(rng::sample_seed(seed, x, y, i));
+                        sampler.start_sample(x, y, i);
+
+                        let (dx, dy) = sampler.get_2d();
+                        let sample_x = f64::from(x) + dx;
+                        let sample_y = f64::from(y) + dy;
 
-                            let u = ((f64::from(sx) + 0.5 + dx) * 0.5 + f64::from(x)) * w;
-                            let v = ((f64::from(sy) + 0.5 + dy) * 0.5 + f64::from(y)) * h;
+                        let ray =
+                            scene
+                                .camera
+                                .get_ray_differential(sample_x * w, sample_y * h, du, dv);
+                        let path = integrator.trace(scene.as_ref(), &ray, max_depth, &mut sampler);
+                        let color = path.radiance();
 
-                            let ray = scene.camera.get_ray(u, v);
+                        aov_values.clear();
+                        aov_values.extend(aovs.iter().map(|&aov| path.aov(aov)));
 
-                            pixel_color += radiance(scene.as_ref(), &ray, 1, max_depth);
-                        }
+                        film_tile.pixel_mut(x, y).add_sample(color);
+                        film_tile.splat(&filter, sample_x, sample_y, color, &aov_values);
                     }
                 }
 
-                pixel_color = pixel_color * s;
+                // The receiver only goes away if the render already failed.
+                let _ = sender.send((index, film_tile));
+            });
+        }
+
+        // Only the workers hold senders now, so the channel disconnects when
+        // the last one finishes or panics.
+        drop(sender);
 
-                let mut img = img.lock().unwrap();
+        let mut received = 0;
+        let mut film_tiles = vec![None; tiles.len()];
 
-                img.put_pixel(x, y, pixel_color.to_gamma_rgb(gamma_correction));
+        for (index, film_tile) in receiver {
+            let pass_traced = film_tile.samples();
 
-                work_count.fetch_add(1, Ordering::SeqCst);
-            });
+            film_tiles[index] = Some(film_tile);
+            traced += pass_traced;
+            received += 1;
+
+            pb.inc(pass_traced);
+        }
+
+        for index in &merge_order {
+            if let Some(film_tile) = &film_tiles[*index] {
+                film.merge_tile(film_tile);
+            }
+        }
+
+        if received < jobs || pool.panic_count() > 0 {
+            return Err(Error::WorkerPanicked);
+        }
+
+        taken += pass;
+
+        if pass_interval.is_some_and(|interval| pass_start.elapsed() < interval) {
+            pass_samples = pass_samples.saturating_mul(2);
+        }
+
+        on_pass(&film, taken, start.elapsed())?;
+
+        if let Some(adaptive) = &settings.adaptive {
+            update_active(&mut active, &film, taken, adaptive);
         }
-    }
 
-    let ten_millis = Duration::from_millis(10);
-    loop {
-        pb.set_position(work_count.as_ref().load(Ordering::SeqCst) as u64);
+        let out_of_time = time_limit.is_some_and(|limit| start.elapsed() >= limit);
+        let done = out_of_time || taken >= samples || !active.contains(&true);
 
-        thread::sleep(ten_millis);
+        if let Some(checkpoint) = &settings.checkpoint {
+            if done || last_checkpoint.elapsed() >= checkpoint.interval {
+                let header = checkpoint::Header {
+                    samples: taken,
+                    ..header.clone()
+                };
 
-        if pool.queued_count() == 0 {
+                checkpoint::write(&checkpoint.path, &header, &film)?;
+                last_checkpoint = Instant::now();
+            }
+        }
+
+        if out_of_time {
             break;
         }
     }
 
-    pool.join();
     pb.finish();
 
-    imgbuf.lock().unwrap().save(filename).unwrap();
-
     let end = start.elapsed();
+    let seconds = f64::from(end.as_secs() as u32) + f64::from(end.subsec_millis()) / 1000.0;
 
     println!();
+    println!("Render took {} seconds.", seconds);
     println!(
-        "Render took {} seconds.",
-        f64::from(end.as_secs() as u32) + f64::from(end.subsec_millis()) / 1000.0
+        "Throughput of {:.0} pixels/s ({:.0} samples/s).",
+        region.area() as f64 / seconds.max(0.001),
+        traced as f64 / seconds.max(0.001)
     );
+
+    Ok(film)
 }
 
-fn radiance(scene: &Scene, ray: &Ray, depth: u32, max_depth: u32) -> Color {
-    if let Some(intersection) = scene.world.intersect(ray, 0.0001, std::f64::INFINITY) {
-        let emitted = intersection
-            .material
-            .emit(intersection.u, intersection.v, intersection.p);
-
-        if let Some(scattered) = intersection.material.scatter(ray, &intersection) {
-            let mut attenuation = scattered.attenuation;
-            let p = (attenuation.r + attenuation.g + attenuation.b) / 3.0;
-
-            if depth > 5 {
-                if rng::get_random_number() < p {
-                    attenuation = attenuation / p;
-                } else {
-                    return emitted;
-                }
-            }
+/// Stops sampling the pixels adaptive sampling considers converged once every
+/// pixel took the minimum samples.
+fn update_active(active: &mut [bool], film: &Film, taken: u32, adaptive: &AdaptiveSampling) {
+    if taken < adaptive.min_samples {
+        return;
+    }
 
-            if depth < 100 {
-                emitted + attenuation * radiance(scene, &scattered.scattered, depth + 1, max_depth)
-            } else {
-                emitted
-            }
-        } else {
-            emitted
-        }
-    } else {
-        Color::new(0.0, 0.0, 0.0)
+    for (active, pixel) in active.iter_mut().zip(film.pixels()) {
+        *active = *active && pixel.relative_error() > adaptive.threshold;
     }
 }
 
-fn tent_filter_factor() -> f64 {
-    let r = 2.0 * rng::get_random_number();
+/// Gamma corrected image of the film, denoised if the settings say so.
+fn film_image(film: &Film, settings: &RenderSettings) -> image::RgbImage {
+    let gamma_correction = settings.gamma.recip();
 
-    if r < 1.0 {
-        r.sqrt() - 1.0
-    } else {
-        1.0 - (2.0 - r).sqrt()
+    match &settings.denoise {
+        Some(denoise) => film.to_denoised_image(denoise, gamma_correction),
+        None => film.to_image(gamma_correction),
     }
 }
+
+/// Saves the AOV layers of the film as EXR images named after `filename`.
+fn save_aovs(film: &Film, filename: &str) -> Result<(), Error> {
+    for (index, layer) in film.layers().iter().enumerate() {
+        let path = Path::new(filename).with_extension(format!("{}.exr", layer.aov.name()));
+
+        save_image(&film.aov_image(index), path)?;
+    }
+
+    Ok(())
+}
+
+fn save_image<P, C>(image: &image::ImageBuffer<P, C>, path: impl AsRef<Path>) -> Result<(), Error>
+where
+    P: image::PixelWithColorType,
+    [P::Subpixel]: image::EncodableLayout,
+    C: std::ops::Deref<Target = [P::Subpixel]>,
+{
+    image
+        .save(path.as_ref())
+        .map_err(|source| Error::ImageSave {
+            path: path.as_ref().to_path_buf(),
+            source,
+        })
+}
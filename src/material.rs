@@ -1,19 +1,35 @@
 use crate::intersectable::Intersection;
 use crate::ray::Ray;
-use crate::rng;
+use crate::sampler::Sampler;
 use crate::Color;
 use crate::Texture;
 use crate::Vec3;
 
+use std::fmt::Debug;
+use std::sync::Arc;
+
+mod bump_map;
+mod clear_coat;
 mod dielectric;
 mod diffuse_light;
 mod lambertian;
 mod metal;
+mod mix;
+mod normal_map;
+mod principled;
+mod subsurface;
 
+use bump_map::BumpMap;
+use clear_coat::ClearCoat;
 use dielectric::Dielectric;
-use diffuse_light::DiffuseLight;
+pub use dielectric::RefractiveIndex;
+pub use diffuse_light::DiffuseLight;
 use lambertian::Lambertian;
 use metal::Metal;
+use mix::Mix;
+use normal_map::NormalMap;
+pub use principled::Principled;
+use subsurface::Subsurface;
 
 /// Material object.
 ///
@@ -22,23 +38,132 @@ use metal::Metal;
 /// and 1.0 and they're clamped when converted to `Rgb` it doens't mean they
 /// can't be declared to have larger values if needed to. This is usually the
 /// case for light intensity.
+///
+/// Built-in materials are dispatched without going through a trait object,
+/// materials defined outside of the path tracer are wrapped by
+/// `Material::custom`.
 #[derive(Clone, Debug)]
 pub enum Material {
     Lambertian(Lambertian),
     Dielectric(Dielectric),
     Metal(Metal),
     DiffuseLight(DiffuseLight),
+    NormalMap(NormalMap),
+    BumpMap(BumpMap),
+    Mix(Mix),
+    ClearCoat(ClearCoat),
+    Principled(Box<Principled>),
+    Subsurface(Subsurface),
+    Custom(Arc<dyn Scatterable>),
 }
 
+/// Direction picked by `Scatterable::sample`.
+///
+/// # Members
+/// * `scattered` - Ray leaving the intersection in the picked direction.
+/// * `attenuation` - Weight of the light coming back along `scattered`, the
+///   value of `Scatterable::evaluate` for the direction over its `pdf`.
+/// * `pdf` - Probability density, over solid angle, of having picked the
+///   direction. Zero for directions that can only be sampled, like the
+///   reflection of a mirror, which `evaluate` and `pdf` leave out.
+/// * `lobe` - Kind of reflection the direction was picked from.
 #[derive(Debug)]
 pub struct Scattered {
     pub scattered: Ray,
     pub attenuation: Color,
+    pub pdf: f64,
+    pub lobe: Lobe,
+}
+
+/// Participating medium enclosed by a surface, see `Scatterable::medium`.
+///
+/// # Members
+/// * `absorption` - Absorption coefficient, fraction of the light absorbed
+///   per unit of distance.
+/// * `scattering` - Scattering coefficient, fraction of the light deflected
+///   per unit of distance.
+/// * `anisotropy` - Mean cosine of the angle light is deflected by, from
+///   -1.0 scattering it back to 1.0 letting it through, 0.0 scattering it
+///   the same in every direction (Henyey-Greenstein).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Medium {
+    pub absorption: Color,
+    pub scattering: Color,
+    pub anisotropy: f64,
 }
 
-pub trait Scatterable {
-    fn emit(&self, u: f64, v: f64, p: Vec3) -> Color;
-    fn scatter(&self, ray: &Ray, intersection: &Intersection) -> Option<Scattered>;
+/// Kind of reflection a material scattered a ray with.
+///
+/// * `Diffuse` - Scattered in every direction, like a matte surface.
+/// * `Specular` - Reflected or refracted around a single direction, like
+///   metal or glass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lobe {
+    Diffuse,
+    Specular,
+}
+
+/// How a surface scatters and emits light, its BSDF.
+///
+/// Implement it to add a material to the path tracer, see `Material::custom`.
+/// In every method `ray` is the ray that hit the surface and `intersection`
+/// where it did. Directions don't need to be normalized.
+///
+/// The `Debug` output of a material goes into `Scene::fingerprint`, two
+/// materials that scatter light differently must print differently or
+/// resuming a checkpoint of a changed scene goes unnoticed.
+pub trait Scatterable: Debug + Send + Sync {
+    /// Light emitted by the surface back along `ray`.
+    fn emit(&self, _ray: &Ray, _intersection: &Intersection) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    /// Medium enclosed by the surface where it's entered at `intersection`,
+    /// `None` if it doesn't enclose one.
+    ///
+    /// Rays going through the surface inwards, against its normal, travel
+    /// through the medium until they go out through another surface
+    /// enclosing a medium. On the way they are absorbed following the
+    /// Beer-Lambert law and scattered around, see `Medium`.
+    fn medium(&self, _intersection: &Intersection) -> Option<Medium> {
+        None
+    }
+
+    /// Whether `sample` picks directions depending on `Ray::wavelength`, like
+    /// a prism does. The spectral integrator then follows only the hero
+    /// wavelength of the path from the surface on.
+    fn is_dispersive(&self) -> bool {
+        false
+    }
+
+    /// Whether the surface emits light at all. Emissive shapes are sampled as
+    /// lights if they support it, see `Intersectable::sample_surface`.
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    /// Picks a direction for the ray to continue in, `None` if the surface
+    /// absorbs it.
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered>;
+
+    /// Fraction of the light arriving from `direction` scattered back along
+    /// `ray`, times the cosine between `direction` and the normal. Directions
+    /// that can only be sampled are left out, so it's zero for materials like
+    /// mirrors and glass.
+    fn evaluate(&self, _ray: &Ray, _intersection: &Intersection, _direction: Vec3) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    /// Probability density, over solid angle, of `sample` picking
+    /// `direction`, leaving out directions that can only be sampled.
+    fn pdf(&self, _ray: &Ray, _intersection: &Intersection, _direction: Vec3) -> f64 {
+        0.0
+    }
 }
 
 impl Material {
@@ -51,39 +176,239 @@ impl Material {
     }
 
     pub fn dielectric(attenuation: Texture, refractive_index: f64) -> Material {
+        Material::absorbing_dielectric(attenuation, refractive_index, Color::default())
+    }
+
+    /// Dielectric whose inside absorbs `absorption` of the light per unit of
+    /// distance, see `Color::absorption_at_distance` to pick it by the color
+    /// the material takes.
+    pub fn absorbing_dielectric(
+        attenuation: Texture,
+        refractive_index: f64,
+        absorption: Color,
+    ) -> Material {
+        Material::dispersive_dielectric(
+            attenuation,
+            RefractiveIndex::Constant(refractive_index),
+            absorption,
+        )
+    }
+
+    /// Dielectric whose refractive index may change with the wavelength,
+    /// absorbing `absorption` of the light per unit of distance inside like
+    /// `Material::absorbing_dielectric`.
+    pub fn dispersive_dielectric(
+        attenuation: Texture,
+        refractive_index: RefractiveIndex,
+        absorption: Color,
+    ) -> Material {
         Material::Dielectric(Dielectric {
             attenuation,
             refractive_index,
+            absorption,
         })
     }
 
+    /// One sided light of the color of `texture`, see `DiffuseLight`.
     pub fn diffuse_light(texture: Texture) -> Material {
-        Material::DiffuseLight(DiffuseLight { texture })
+        Material::DiffuseLight(DiffuseLight {
+            texture,
+            ..DiffuseLight::default()
+        })
+    }
+
+    /// Light with the parameters in `light`.
+    pub fn light(light: DiffuseLight) -> Material {
+        Material::DiffuseLight(light)
+    }
+
+    /// Wraps `material` perturbing its shading normal with a tangent space
+    /// normal map.
+    pub fn normal_map(material: Material, map: Texture) -> Material {
+        Material::NormalMap(NormalMap {
+            material: Box::new(material),
+            map,
+        })
+    }
+
+    /// Wraps `material` perturbing its shading normal with a height map, where
+    /// `scale` is the displacement in world units of a texture value of 1.0.
+    pub fn bump_map(material: Material, height: Texture, scale: f64) -> Material {
+        Material::BumpMap(BumpMap {
+            material: Box::new(material),
+            height,
+            scale,
+        })
+    }
+
+    /// Blends `a` into `b` by the value of `factor` at each point, all `a`
+    /// where it's 0.0 and all `b` where it's 1.0.
+    pub fn mix(a: Material, b: Material, factor: Texture) -> Material {
+        Material::Mix(Mix {
+            a: Box::new(a),
+            b: Box::new(b),
+            factor,
+        })
+    }
+
+    /// Covers `base` with a clear coating of the given refractive index,
+    /// whose reflection is blurred by `roughness`, from 0.0 to 1.0.
+    pub fn clear_coat(base: Material, refractive_index: f64, roughness: f64) -> Material {
+        Material::ClearCoat(ClearCoat {
+            base: Box::new(base),
+            refractive_index,
+            roughness,
+        })
+    }
+
+    /// Disney's all purpose material, see `Principled` for its parameters.
+    pub fn principled(principled: Principled) -> Material {
+        Material::Principled(Box::new(principled))
+    }
+
+    /// Translucent material scattering light under its surface, like wax,
+    /// skin or marble. It should cover closed shapes.
+    ///
+    /// # Arguments
+    ///
+    /// * `albedo` - Color the material takes.
+    /// * `mean_free_path` - Average distance light travels inside before
+    ///   being scattered, for each color. The larger it is the deeper light
+    ///   goes in and the more translucent the material looks.
+    /// * `anisotropy` - Mean cosine of the angle light is scattered by
+    ///   inside, see `Medium`.
+    pub fn subsurface(albedo: Texture, mean_free_path: Color, anisotropy: f64) -> Material {
+        Material::Subsurface(Subsurface {
+            albedo,
+            mean_free_path,
+            anisotropy,
+            refractive_index: 1.4,
+        })
+    }
+
+    /// A material defined outside of the path tracer.
+    pub fn custom(material: Arc<dyn Scatterable>) -> Material {
+        Material::Custom(material)
+    }
+}
+
+impl Scatterable for Material {
+    fn emit(&self, ray: &Ray, intersection: &Intersection) -> Color {
+        match self {
+            Material::DiffuseLight(light) => light.emit(ray, intersection),
+            Material::NormalMap(normal_map) => normal_map.emit(ray, intersection),
+            Material::BumpMap(bump_map) => bump_map.emit(ray, intersection),
+            Material::Mix(mix) => mix.emit(ray, intersection),
+            Material::ClearCoat(clear_coat) => clear_coat.emit(ray, intersection),
+            Material::Custom(custom) => custom.emit(ray, intersection),
+            _ => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    fn is_emissive(&self) -> bool {
+        match self {
+            Material::DiffuseLight(light) => light.is_emissive(),
+            Material::NormalMap(normal_map) => normal_map.material.is_emissive(),
+            Material::BumpMap(bump_map) => bump_map.material.is_emissive(),
+            Material::Mix(mix) => mix.a.is_emissive() || mix.b.is_emissive(),
+            Material::ClearCoat(clear_coat) => clear_coat.base.is_emissive(),
+            Material::Custom(custom) => custom.is_emissive(),
+            _ => false,
+        }
+    }
+
+    fn medium(&self, intersection: &Intersection) -> Option<Medium> {
+        match self {
+            Material::Dielectric(dielectric) => dielectric.medium(intersection),
+            Material::NormalMap(normal_map) => normal_map.material.medium(intersection),
+            Material::BumpMap(bump_map) => bump_map.material.medium(intersection),
+            Material::Mix(mix) => mix.medium(intersection),
+            Material::ClearCoat(clear_coat) => clear_coat.base.medium(intersection),
+            Material::Subsurface(subsurface) => subsurface.medium(intersection),
+            Material::Custom(custom) => custom.medium(intersection),
+            _ => None,
+        }
+    }
+
+    fn is_dispersive(&self) -> bool {
+        match self {
+            Material::Dielectric(dielectric) => dielectric.is_dispersive(),
+            Material::NormalMap(normal_map) => normal_map.material.is_dispersive(),
+            Material::BumpMap(bump_map) => bump_map.material.is_dispersive(),
+            Material::Mix(mix) => mix.a.is_dispersive() || mix.b.is_dispersive(),
+            Material::ClearCoat(clear_coat) => clear_coat.base.is_dispersive(),
+            Material::Custom(custom) => custom.is_dispersive(),
+            _ => false,
+        }
     }
 
-    pub fn emit(&self, u: f64, v: f64, p: Vec3) -> Color {
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
         match self {
-            Material::DiffuseLight(light) => light.emit(u, v, p),
+            Material::Lambertian(lambertian) => lambertian.sample(ray, intersection, sampler),
+            Material::Metal(metal) => metal.sample(ray, intersection, sampler),
+            Material::Dielectric(dielectric) => dielectric.sample(ray, intersection, sampler),
+            Material::DiffuseLight(diffuse_light) => {
+                diffuse_light.sample(ray, intersection, sampler)
+            }
+            Material::NormalMap(normal_map) => normal_map.sample(ray, intersection, sampler),
+            Material::BumpMap(bump_map) => bump_map.sample(ray, intersection, sampler),
+            Material::Mix(mix) => mix.sample(ray, intersection, sampler),
+            Material::ClearCoat(clear_coat) => clear_coat.sample(ray, intersection, sampler),
+            Material::Principled(principled) => principled.sample(ray, intersection, sampler),
+            Material::Subsurface(subsurface) => subsurface.sample(ray, intersection, sampler),
+            Material::Custom(custom) => custom.sample(ray, intersection, sampler),
+        }
+    }
+
+    fn evaluate(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> Color {
+        match self {
+            Material::Lambertian(lambertian) => lambertian.evaluate(ray, intersection, direction),
+            Material::NormalMap(normal_map) => normal_map.evaluate(ray, intersection, direction),
+            Material::BumpMap(bump_map) => bump_map.evaluate(ray, intersection, direction),
+            Material::Mix(mix) => mix.evaluate(ray, intersection, direction),
+            Material::ClearCoat(clear_coat) => clear_coat.evaluate(ray, intersection, direction),
+            Material::Principled(principled) => principled.evaluate(ray, intersection, direction),
+            Material::Subsurface(subsurface) => subsurface.evaluate(ray, intersection, direction),
+            Material::Custom(custom) => custom.evaluate(ray, intersection, direction),
             _ => Color::new(0.0, 0.0, 0.0),
         }
     }
 
-    pub fn scatter(&self, ray: &Ray, intersection: &Intersection) -> Option<Scattered> {
+    fn pdf(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> f64 {
         match self {
-            Material::Lambertian(lambertian) => lambertian.scatter(ray, intersection),
-            Material::Metal(metal) => metal.scatter(ray, intersection),
-            Material::Dielectric(dielectric) => dielectric.scatter(ray, intersection),
-            Material::DiffuseLight(diffuse_light) => diffuse_light.scatter(ray, intersection),
+            Material::Lambertian(lambertian) => lambertian.pdf(ray, intersection, direction),
+            Material::NormalMap(normal_map) => normal_map.pdf(ray, intersection, direction),
+            Material::BumpMap(bump_map) => bump_map.pdf(ray, intersection, direction),
+            Material::Mix(mix) => mix.pdf(ray, intersection, direction),
+            Material::ClearCoat(clear_coat) => clear_coat.pdf(ray, intersection, direction),
+            Material::Principled(principled) => principled.pdf(ray, intersection, direction),
+            Material::Subsurface(subsurface) => subsurface.pdf(ray, intersection, direction),
+            Material::Custom(custom) => custom.pdf(ray, intersection, direction),
+            _ => 0.0,
         }
     }
 }
 
-fn random_in_unit_sphere() -> Vec3 {
-    let u = rng::get_random_number();
-    let v = rng::get_random_number();
+/// Uniformly distributed direction.
+fn random_unit_vector(sampler: &mut Sampler) -> Vec3 {
+    let (u, v) = sampler.get_2d();
+    let z = 1.0 - 2.0 * u;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * std::f64::consts::PI * v;
+
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+fn random_in_unit_sphere(sampler: &mut Sampler) -> Vec3 {
+    let (u, v) = sampler.get_2d();
     let theta = u * 2.0 * std::f64::consts::PI;
     let phi = (2.0 * v - 1.0).acos();
-    let r = rng::get_random_number().cbrt();
+    let r = sampler.get_1d().cbrt();
     let sin_theta = theta.sin();
     let cos_theta = theta.cos();
     let sin_phi = phi.sin();
@@ -92,6 +417,14 @@ fn random_in_unit_sphere() -> Vec3 {
     Vec3::new(r * sin_phi * cos_theta, r * sin_phi * sin_theta, cos_phi)
 }
 
+/// Schlick's approximation of the Fresnel reflectance of a dielectric, for
+/// light arriving at `cosine` with the normal.
+fn schlick(cosine: f64, ref_idx: f64) -> f64 {
+    let r0 = ((1.0 - ref_idx) / (1.0 + ref_idx)).powi(2);
+
+    r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
+}
+
 fn reflect(v: Vec3, n: Vec3) -> Vec3 {
     v - 2.0 * v.dot(n) * n
 }
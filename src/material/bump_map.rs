@@ -0,0 +1,94 @@
+use super::{Material, Scatterable, Scattered};
+use crate::intersectable::Intersection;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::Color;
+use crate::Texture;
+use crate::Vec3;
+
+/// Perturbs the shading normal of a material as if the surface was displaced
+/// along its normal by a height texture.
+///
+/// The height is the average of the texture channels times `scale`, in world
+/// units.
+#[derive(Clone, Debug)]
+pub struct BumpMap {
+    pub material: Box<Material>,
+    pub height: Texture,
+    pub scale: f64,
+}
+
+impl BumpMap {
+    fn displacement(&self, u: f64, v: f64, p: Vec3, width: f64) -> f64 {
+        let color = self.height.filtered_value(u, v, p, width);
+
+        (color.r + color.g + color.b) / 3.0 * self.scale
+    }
+
+    fn shade<'a>(&self, intersection: &Intersection<'a>) -> Intersection<'a> {
+        let (u, v, p) = (intersection.u, intersection.v, intersection.p);
+        let width = intersection.uv_width();
+
+        // Finite differences over the pixel footprint, or a small fixed step
+        // when the ray carried no differentials.
+        let mut du = 0.5 * (intersection.dudx.abs() + intersection.dudy.abs());
+        let mut dv = 0.5 * (intersection.dvdx.abs() + intersection.dvdy.abs());
+
+        if du == 0.0 {
+            du = 0.0005;
+        }
+
+        if dv == 0.0 {
+            dv = 0.0005;
+        }
+
+        let displace = self.displacement(u, v, p, width);
+        let u_displace = self.displacement(u + du, v, p + intersection.dpdu * du, width);
+        let v_displace = self.displacement(u, v + dv, p + intersection.dpdv * dv, width);
+
+        let n = intersection.normal.normalize();
+        let dpdu = intersection.dpdu + n * ((u_displace - displace) / du);
+        let dpdv = intersection.dpdv + n * ((v_displace - displace) / dv);
+
+        let mut normal = dpdu.cross(dpdv).normalize();
+
+        if !normal.is_finite() {
+            return intersection.clone();
+        }
+
+        if normal.dot(n) < 0.0 {
+            normal = -normal;
+        }
+
+        let mut shaded = intersection.clone();
+        shaded.normal = normal;
+        shaded.dpdu = dpdu;
+        shaded.dpdv = dpdv;
+        shaded
+    }
+}
+
+impl Scatterable for BumpMap {
+    fn emit(&self, ray: &Ray, intersection: &Intersection) -> Color {
+        self.material.emit(ray, &self.shade(intersection))
+    }
+
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
+        self.material
+            .sample(ray, &self.shade(intersection), sampler)
+    }
+
+    fn evaluate(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> Color {
+        self.material
+            .evaluate(ray, &self.shade(intersection), direction)
+    }
+
+    fn pdf(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> f64 {
+        self.material.pdf(ray, &self.shade(intersection), direction)
+    }
+}
@@ -0,0 +1,85 @@
+use super::{Lobe, Material, Scatterable, Scattered};
+use crate::intersectable::Intersection;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::Color;
+use crate::Vec3;
+
+/// A clear dielectric layer, like varnish or car paint, over another
+/// material.
+///
+/// Light is reflected off the coating as given by the Fresnel equations for
+/// its refractive index, the rest reaches the base material. `roughness`
+/// blurs the reflection like the fuzz of `Metal`, 0.0 being a perfect
+/// mirror.
+#[derive(Clone, Debug)]
+pub struct ClearCoat {
+    pub base: Box<Material>,
+    pub refractive_index: f64,
+    pub roughness: f64,
+}
+
+impl ClearCoat {
+    /// Fraction of the light reflected by the coating towards `ray`.
+    fn reflectance(&self, ray: &Ray, intersection: &Intersection) -> f64 {
+        let cosine = ray
+            .direction
+            .normalize()
+            .dot(intersection.normal.normalize())
+            .abs();
+
+        super::schlick(cosine, self.refractive_index)
+    }
+}
+
+impl Scatterable for ClearCoat {
+    fn emit(&self, ray: &Ray, intersection: &Intersection) -> Color {
+        self.base.emit(ray, intersection) * (1.0 - self.reflectance(ray, intersection))
+    }
+
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
+        let reflectance = self.reflectance(ray, intersection);
+
+        if sampler.get_1d() >= reflectance {
+            let mut scattered = self.base.sample(ray, intersection, sampler)?;
+            scattered.pdf *= 1.0 - reflectance;
+
+            return Some(scattered);
+        }
+
+        let normal = if ray.direction.dot(intersection.normal) > 0.0 {
+            -intersection.normal
+        } else {
+            intersection.normal
+        };
+        let reflected = super::reflect(ray.direction.normalize(), normal);
+        let mut direction = reflected + self.roughness * super::random_in_unit_sphere(sampler);
+
+        // Rough reflections can point into the surface, keep those sharp
+        // rather than losing their light.
+        if direction.dot(normal) <= 0.0 {
+            direction = reflected;
+        }
+
+        Some(Scattered {
+            scattered: Ray::new(intersection.p, direction),
+            attenuation: Color::new(1.0, 1.0, 1.0),
+            pdf: 0.0,
+            lobe: Lobe::Specular,
+        })
+    }
+
+    fn evaluate(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> Color {
+        self.base.evaluate(ray, intersection, direction)
+            * (1.0 - self.reflectance(ray, intersection))
+    }
+
+    fn pdf(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> f64 {
+        self.base.pdf(ray, intersection, direction) * (1.0 - self.reflectance(ray, intersection))
+    }
+}
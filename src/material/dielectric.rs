@@ -1,40 +1,114 @@
-use super::{Scatterable, Scattered};
+use super::{Lobe, Medium, Scatterable, Scattered};
 use crate::intersectable::Intersection;
 use crate::ray::Ray;
-use crate::rng;
+use crate::sampler::Sampler;
 use crate::Color;
 use crate::Texture;
 use crate::Vec3;
 
+/// Glass like material, reflecting or refracting rays.
+///
+/// # Members
+/// * `attenuation` - Tint applied every time a ray goes through the surface.
+/// * `refractive_index` - Refractive index of the inside of the surface, by
+///   wavelength for dispersive materials.
+/// * `absorption` - Fraction of each color absorbed per unit of distance
+///   traveled inside, so thick parts look darker than thin ones. Black for
+///   a clear material.
 #[derive(Clone, Debug)]
 pub struct Dielectric {
     pub attenuation: Texture,
-    pub refractive_index: f64,
+    pub refractive_index: RefractiveIndex,
+    pub absorption: Color,
 }
 
-fn schlick(cosine: f64, ref_idx: f64) -> f64 {
-    let r0 = ((1.0 - ref_idx) / (1.0 + ref_idx)).powi(2);
+/// Refractive index of a dielectric, which may change with the wavelength of
+/// the light splitting it into a rainbow, see `Integrator::Spectral`.
+///
+/// * `Constant` - The same index for every wavelength.
+/// * `Cauchy` - Cauchy's equation, `a + b / λ²` with `λ` in micrometers.
+/// * `Sellmeier` - Sellmeier's equation, `n² = 1 + Σ b λ² / (λ² - c)` with
+///   `λ` in micrometers and `c` in micrometers squared.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RefractiveIndex {
+    Constant(f64),
+    Cauchy { a: f64, b: f64 },
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl RefractiveIndex {
+    /// Schott BK7, the usual optical crown glass.
+    pub const BK7: RefractiveIndex = RefractiveIndex::Sellmeier {
+        b: [1.039_612_12, 0.231_792_344, 1.010_469_45],
+        c: [0.006_000_698_67, 0.020_017_914_4, 103.560_653],
+    };
+
+    /// Schott SF11, a dense flint glass with strong dispersion.
+    pub const SF11: RefractiveIndex = RefractiveIndex::Sellmeier {
+        b: [1.737_596_95, 0.313_747_346, 1.898_781_01],
+        c: [0.013_188_707, 0.062_306_814_2, 155.236_29],
+    };
 
-    r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
+    /// Wavelength, in nanometers, the index is taken at when rendering
+    /// colors, the yellow Fraunhofer d line.
+    pub const REFERENCE_WAVELENGTH: f64 = 587.6;
+
+    /// Refractive index at `wavelength` nanometers, or at the reference
+    /// wavelength if `None`.
+    pub fn at(&self, wavelength: Option<f64>) -> f64 {
+        let wavelength = wavelength.unwrap_or(Self::REFERENCE_WAVELENGTH) * 1e-3;
+        let squared = wavelength * wavelength;
+
+        match *self {
+            RefractiveIndex::Constant(index) => index,
+            RefractiveIndex::Cauchy { a, b } => a + b / squared,
+            RefractiveIndex::Sellmeier { b, c } => (1.0
+                + b.iter()
+                    .zip(c.iter())
+                    .map(|(b, c)| b * squared / (squared - c))
+                    .sum::<f64>())
+            .sqrt(),
+        }
+    }
+
+    /// Whether the index changes with the wavelength.
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, RefractiveIndex::Constant(_))
+    }
 }
 
 impl Scatterable for Dielectric {
-    fn emit(&self, _u: f64, _v: f64, _p: Vec3) -> Color {
-        Color::new(0.0, 0.0, 0.0)
+    fn medium(&self, _intersection: &Intersection) -> Option<Medium> {
+        Some(Medium {
+            absorption: self.absorption,
+            ..Medium::default()
+        })
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.refractive_index.is_dispersive()
     }
 
-    fn scatter(&self, ray: &Ray, intersection: &Intersection) -> Option<Scattered> {
-        let ref_idx = self.refractive_index;
-        let attenuation = self
-            .attenuation
-            .value(intersection.u, intersection.v, intersection.p);
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
+        let ref_idx = self.refractive_index.at(ray.wavelength);
+        let attenuation = self.attenuation.filtered_value(
+            intersection.u,
+            intersection.v,
+            intersection.p,
+            intersection.uv_width(),
+        );
         let reflected = super::reflect(ray.direction.normalize(), intersection.normal);
 
         let d = ray.direction.dot(intersection.normal);
 
         let (outward_normal, ni_over_nt, cosine) = if d > 0.0 {
             (
-                intersection.normal * -1.0,
+                -intersection.normal,
                 ref_idx,
                 ref_idx * d / ray.direction.length(),
             )
@@ -42,32 +116,28 @@ impl Scatterable for Dielectric {
             (
                 intersection.normal,
                 1.0 / ref_idx,
-                -1.0 * d / ray.direction.length(),
+                -d / ray.direction.length(),
             )
         };
 
         let (refracted, reflect_probability) =
             if let Some(r) = super::refract(ray.direction, outward_normal, ni_over_nt) {
-                (r, schlick(cosine, ref_idx))
+                (r, super::schlick(cosine, ref_idx))
             } else {
                 (Vec3::zero(), 1.0)
             };
 
-        let scattered = if rng::get_random_number() < reflect_probability {
-            Ray {
-                origin: intersection.p,
-                direction: reflected,
-            }
+        let scattered = if sampler.get_1d() < reflect_probability {
+            Ray::new(intersection.p, reflected)
         } else {
-            Ray {
-                origin: intersection.p,
-                direction: refracted,
-            }
+            Ray::new(intersection.p, refracted)
         };
 
         Some(Scattered {
             scattered,
             attenuation,
+            pdf: 0.0,
+            lobe: Lobe::Specular,
         })
     }
 }
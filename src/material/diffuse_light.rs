@@ -1,21 +1,60 @@
 use super::{Scatterable, Scattered};
 use crate::intersectable::Intersection;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 use crate::Color;
 use crate::Texture;
-use crate::Vec3;
 
+/// Surface emitting light the same in every direction.
+///
+/// # Members
+/// * `texture` - Color of the emitted light, see `Color::blackbody` for the
+///   color of a temperature.
+/// * `strength` - Multiplier of the color.
+/// * `two_sided` - Whether light is emitted from the back of the surface too,
+///   the side its normal points away from. Lights seen from the inside, like
+///   a sky sphere around the scene, need it.
 #[derive(Clone, Debug)]
 pub struct DiffuseLight {
     pub texture: Texture,
+    pub strength: f64,
+    pub two_sided: bool,
+}
+
+impl Default for DiffuseLight {
+    fn default() -> Self {
+        DiffuseLight {
+            texture: Texture::constant_color(Color::new(1.0, 1.0, 1.0)),
+            strength: 1.0,
+            two_sided: false,
+        }
+    }
 }
 
 impl Scatterable for DiffuseLight {
-    fn emit(&self, u: f64, v: f64, p: Vec3) -> Color {
-        self.texture.value(u, v, p)
+    fn emit(&self, ray: &Ray, intersection: &Intersection) -> Color {
+        if !self.two_sided && ray.direction.dot(intersection.normal) > 0.0 {
+            return Color::default();
+        }
+
+        self.texture.filtered_value(
+            intersection.u,
+            intersection.v,
+            intersection.p,
+            intersection.uv_width(),
+        ) * self.strength
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
     }
 
-    fn scatter(&self, _ray: &Ray, _intersection: &Intersection) -> Option<Scattered> {
+    fn sample(
+        &self,
+        _ray: &Ray,
+        _intersection: &Intersection,
+        _sampler: &mut Sampler,
+    ) -> Option<Scattered> {
         None
     }
 }
@@ -1,33 +1,62 @@
-use super::{Scatterable, Scattered};
+use super::{Lobe, Scatterable, Scattered};
 use crate::intersectable::Intersection;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 use crate::Color;
 use crate::Texture;
 use crate::Vec3;
 
+use std::f64::consts::PI;
+
 #[derive(Clone, Debug)]
 pub struct Lambertian {
     pub albedo: Texture,
 }
 
-impl Scatterable for Lambertian {
-    fn emit(&self, _u: f64, _v: f64, _p: Vec3) -> Color {
-        Color::new(0.0, 0.0, 0.0)
+impl Lambertian {
+    fn albedo(&self, intersection: &Intersection) -> Color {
+        self.albedo.filtered_value(
+            intersection.u,
+            intersection.v,
+            intersection.p,
+            intersection.uv_width(),
+        )
     }
+}
+
+impl Scatterable for Lambertian {
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
+        let normal = intersection.normal.normalize();
 
-    fn scatter(&self, _ray: &Ray, intersection: &Intersection) -> Option<Scattered> {
-        let target = intersection.p + intersection.normal + super::random_in_unit_sphere();
+        // A point on the unit sphere tangent to the surface is a direction
+        // distributed by its cosine with the normal.
+        let mut direction = normal + super::random_unit_vector(sampler);
 
-        let scattered = Ray {
-            origin: intersection.p,
-            direction: target - intersection.p,
-        };
+        if direction.norm() < 1e-12 {
+            direction = normal;
+        }
 
         Some(Scattered {
-            scattered,
-            attenuation: self
-                .albedo
-                .value(intersection.u, intersection.v, intersection.p),
+            scattered: Ray::new(intersection.p, direction),
+            // The cosine and 1 / π of the BSDF cancel out with the pdf.
+            attenuation: self.albedo(intersection),
+            pdf: self.pdf(ray, intersection, direction),
+            lobe: Lobe::Diffuse,
         })
     }
+
+    fn evaluate(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> Color {
+        self.albedo(intersection) * self.pdf(ray, intersection, direction)
+    }
+
+    fn pdf(&self, _ray: &Ray, intersection: &Intersection, direction: Vec3) -> f64 {
+        let cosine = intersection.normal.normalize().dot(direction.normalize());
+
+        cosine.max(0.0) / PI
+    }
 }
@@ -1,9 +1,8 @@
-use super::{Scatterable, Scattered};
+use super::{Lobe, Scatterable, Scattered};
 use crate::intersectable::Intersection;
 use crate::ray::Ray;
-use crate::Color;
+use crate::sampler::Sampler;
 use crate::Texture;
-use crate::Vec3;
 
 #[derive(Clone, Debug)]
 pub struct Metal {
@@ -12,24 +11,30 @@ pub struct Metal {
 }
 
 impl Scatterable for Metal {
-    fn emit(&self, _u: f64, _v: f64, _p: Vec3) -> Color {
-        Color::new(0.0, 0.0, 0.0)
-    }
-
-    fn scatter(&self, ray: &Ray, intersection: &Intersection) -> Option<Scattered> {
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
         let reflected = super::reflect(ray.direction.normalize(), intersection.normal);
 
-        let scattered = Ray {
-            origin: intersection.p,
-            direction: reflected + (self.fuzz * super::random_in_unit_sphere()),
-        };
+        let scattered = Ray::new(
+            intersection.p,
+            reflected + (self.fuzz * super::random_in_unit_sphere(sampler)),
+        );
 
         if scattered.direction.dot(intersection.normal) > 0.0 {
             Some(Scattered {
                 scattered,
-                attenuation: self
-                    .albedo
-                    .value(intersection.u, intersection.v, intersection.p),
+                attenuation: self.albedo.filtered_value(
+                    intersection.u,
+                    intersection.v,
+                    intersection.p,
+                    intersection.uv_width(),
+                ),
+                pdf: 0.0,
+                lobe: Lobe::Specular,
             })
         } else {
             None
@@ -0,0 +1,100 @@
+use super::{Material, Medium, Scatterable, Scattered};
+use crate::intersectable::Intersection;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::Color;
+use crate::Texture;
+use crate::Vec3;
+
+/// Blends two materials, `factor` going from all `a` at 0.0 to all `b` at
+/// 1.0. The factor is the average of the texture channels.
+///
+/// Each scattered ray picks one of the materials at random with the
+/// probability of its weight. The enclosed medium blends the coefficients of
+/// the media of both materials, a material without one counting as empty
+/// space.
+#[derive(Clone, Debug)]
+pub struct Mix {
+    pub a: Box<Material>,
+    pub b: Box<Material>,
+    pub factor: Texture,
+}
+
+impl Mix {
+    fn factor(&self, intersection: &Intersection) -> f64 {
+        let color = self.factor.filtered_value(
+            intersection.u,
+            intersection.v,
+            intersection.p,
+            intersection.uv_width(),
+        );
+
+        ((color.r + color.g + color.b) / 3.0).clamp(0.0, 1.0)
+    }
+}
+
+impl Scatterable for Mix {
+    fn emit(&self, ray: &Ray, intersection: &Intersection) -> Color {
+        let factor = self.factor(intersection);
+
+        self.a.emit(ray, intersection) * (1.0 - factor) + self.b.emit(ray, intersection) * factor
+    }
+
+    fn medium(&self, intersection: &Intersection) -> Option<Medium> {
+        let (a, b) = match (self.a.medium(intersection), self.b.medium(intersection)) {
+            (None, None) => return None,
+            (a, b) => (a.unwrap_or_default(), b.unwrap_or_default()),
+        };
+
+        let factor = self.factor(intersection);
+
+        Some(Medium {
+            absorption: a.absorption * (1.0 - factor) + b.absorption * factor,
+            scattering: a.scattering * (1.0 - factor) + b.scattering * factor,
+            anisotropy: a.anisotropy * (1.0 - factor) + b.anisotropy * factor,
+        })
+    }
+
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
+        let material = if sampler.get_1d() < self.factor(intersection) {
+            &self.b
+        } else {
+            &self.a
+        };
+
+        let mut scattered = material.sample(ray, intersection, sampler)?;
+
+        // Weigh directions by both materials, either of them could have
+        // picked it, unless it's one that can only be sampled.
+        if scattered.pdf > 0.0 {
+            let direction = scattered.scattered.direction;
+            let pdf = self.pdf(ray, intersection, direction);
+
+            if pdf > 0.0 {
+                scattered.attenuation = self.evaluate(ray, intersection, direction) / pdf;
+                scattered.pdf = pdf;
+            }
+        }
+
+        Some(scattered)
+    }
+
+    fn evaluate(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> Color {
+        let factor = self.factor(intersection);
+
+        self.a.evaluate(ray, intersection, direction) * (1.0 - factor)
+            + self.b.evaluate(ray, intersection, direction) * factor
+    }
+
+    fn pdf(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> f64 {
+        let factor = self.factor(intersection);
+
+        self.a.pdf(ray, intersection, direction) * (1.0 - factor)
+            + self.b.pdf(ray, intersection, direction) * factor
+    }
+}
@@ -0,0 +1,76 @@
+use super::{Material, Scatterable, Scattered};
+use crate::intersectable::Intersection;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::Color;
+use crate::Texture;
+use crate::Vec3;
+
+/// Perturbs the shading normal of a material with a tangent space normal map.
+///
+/// The map stores the normal's tangent, bitangent and normal components in
+/// its red, green and blue channels, remapped from [-1, 1] to [0, 1].
+#[derive(Clone, Debug)]
+pub struct NormalMap {
+    pub material: Box<Material>,
+    pub map: Texture,
+}
+
+impl NormalMap {
+    fn shade<'a>(&self, intersection: &Intersection<'a>) -> Intersection<'a> {
+        let color = self.map.filtered_value(
+            intersection.u,
+            intersection.v,
+            intersection.p,
+            intersection.uv_width(),
+        );
+        let local = Vec3::new(
+            2.0 * color.r - 1.0,
+            2.0 * color.g - 1.0,
+            2.0 * color.b - 1.0,
+        );
+
+        let n = intersection.normal.normalize();
+        let t = (intersection.dpdu - n * n.dot(intersection.dpdu)).normalize();
+        let mut b = n.cross(t);
+
+        if b.dot(intersection.dpdv) < 0.0 {
+            b = -b;
+        }
+
+        let normal = (t * local.x + b * local.y + n * local.z).normalize();
+
+        if !normal.is_finite() {
+            return intersection.clone();
+        }
+
+        let mut shaded = intersection.clone();
+        shaded.normal = normal;
+        shaded
+    }
+}
+
+impl Scatterable for NormalMap {
+    fn emit(&self, ray: &Ray, intersection: &Intersection) -> Color {
+        self.material.emit(ray, &self.shade(intersection))
+    }
+
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
+        self.material
+            .sample(ray, &self.shade(intersection), sampler)
+    }
+
+    fn evaluate(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> Color {
+        self.material
+            .evaluate(ray, &self.shade(intersection), direction)
+    }
+
+    fn pdf(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> f64 {
+        self.material.pdf(ray, &self.shade(intersection), direction)
+    }
+}
@@ -0,0 +1,392 @@
+use super::{Lobe, Scatterable, Scattered};
+use crate::intersectable::Intersection;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::Color;
+use crate::Texture;
+use crate::Vec3;
+
+use std::f64::consts::PI;
+
+/// Roughness of the clear coat layer, in the GTR1 distribution of the
+/// Disney BRDF.
+const CLEARCOAT_ALPHA: f64 = 0.001;
+
+/// Lowest roughness of the specular lobe, rougher than this is a mirror as
+/// far as floating point numbers are concerned.
+const MIN_ALPHA: f64 = 0.001;
+
+/// All purpose material after the Disney principled BRDF, from Burley,
+/// "Physically Based Shading at Disney".
+///
+/// Every parameter but the base color is a number, the average of the
+/// channels of its texture, going from 0.0 to 1.0.
+///
+/// # Members
+/// * `base_color` - Color of the diffuse reflection, or of the specular
+///   reflection for metals.
+/// * `metallic` - Blends from a dielectric, like plastic, to a metal.
+/// * `roughness` - From mirror like to matte reflections.
+/// * `specular` - Strength of the specular reflection of dielectrics, 0.5 is
+///   a 4% reflection head on, the usual for most materials.
+/// * `specular_tint` - Tints the specular reflection of dielectrics with the
+///   base color.
+/// * `sheen` - Extra reflection at grazing angles, for cloth.
+/// * `clearcoat` - Strength of a glossy clear coat on top.
+/// * `transmission` - Blends from an opaque surface to glass, tinted by the
+///   base color and blurred by the roughness.
+/// * `anisotropic` - Stretches the specular highlight along the `u` texture
+///   direction, like brushed metal.
+/// * `refractive_index` - Refractive index of the transmitted light.
+#[derive(Clone, Debug)]
+pub struct Principled {
+    pub base_color: Texture,
+    pub metallic: Texture,
+    pub roughness: Texture,
+    pub specular: Texture,
+    pub specular_tint: Texture,
+    pub sheen: Texture,
+    pub clearcoat: Texture,
+    pub transmission: Texture,
+    pub anisotropic: Texture,
+    pub refractive_index: f64,
+}
+
+impl Default for Principled {
+    fn default() -> Self {
+        Principled {
+            base_color: Texture::constant_color(Color::new(0.8, 0.8, 0.8)),
+            metallic: constant(0.0),
+            roughness: constant(0.5),
+            specular: constant(0.5),
+            specular_tint: constant(0.0),
+            sheen: constant(0.0),
+            clearcoat: constant(0.0),
+            transmission: constant(0.0),
+            anisotropic: constant(0.0),
+            refractive_index: 1.5,
+        }
+    }
+}
+
+fn constant(value: f64) -> Texture {
+    Texture::constant_color(Color::new(value, value, value))
+}
+
+/// Parameters of the material at a hit, and the shading frame they are
+/// evaluated in.
+struct Shading {
+    base_color: Color,
+    metallic: f64,
+    roughness: f64,
+    specular: f64,
+    specular_tint: f64,
+    sheen: f64,
+    clearcoat: f64,
+    transmission: f64,
+    alpha_x: f64,
+    alpha_y: f64,
+    tangent: Vec3,
+    bitangent: Vec3,
+    normal: Vec3,
+}
+
+impl Shading {
+    fn to_local(&self, v: Vec3) -> Vec3 {
+        Vec3::new(
+            v.dot(self.tangent),
+            v.dot(self.bitangent),
+            v.dot(self.normal),
+        )
+    }
+
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        self.tangent * v.x + self.bitangent * v.y + self.normal * v.z
+    }
+
+    /// Probabilities of sampling the diffuse, specular and clear coat lobes
+    /// when the ray isn't transmitted.
+    fn lobe_probabilities(&self) -> (f64, f64, f64) {
+        let diffuse = 1.0 - self.metallic;
+        let specular = 1.0;
+        let clearcoat = 0.25 * self.clearcoat;
+        let total = diffuse + specular + clearcoat;
+
+        (diffuse / total, specular / total, clearcoat / total)
+    }
+
+    /// Reflected light for `wo` and `wi` in the shading frame, both
+    /// normalized, before the cosine.
+    fn brdf(&self, wo: Vec3, wi: Vec3) -> Color {
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return Color::default();
+        }
+
+        let h = (wo + wi).normalize();
+        let cos_d = wi.dot(h);
+
+        let luminance = self.base_color.luminance();
+        let tint = if luminance > 0.0 {
+            self.base_color / luminance
+        } else {
+            Color::new(1.0, 1.0, 1.0)
+        };
+
+        let f_l = schlick_weight(wi.z);
+        let f_v = schlick_weight(wo.z);
+        let f_h = schlick_weight(cos_d);
+
+        // Diffuse with Burley's retro-reflection at grazing angles.
+        let fd90 = 0.5 + 2.0 * cos_d * cos_d * self.roughness;
+        let fd = (1.0 + (fd90 - 1.0) * f_l) * (1.0 + (fd90 - 1.0) * f_v);
+        let sheen = lerp(Color::new(1.0, 1.0, 1.0), tint, 0.5) * (f_h * self.sheen);
+        let diffuse = (self.base_color * (fd / PI) + sheen) * (1.0 - self.metallic);
+
+        let specular_color = lerp(
+            lerp(Color::new(1.0, 1.0, 1.0), tint, self.specular_tint) * (self.specular * 0.08),
+            self.base_color,
+            self.metallic,
+        );
+        let fs = lerp(specular_color, Color::new(1.0, 1.0, 1.0), f_h);
+        let gs = smith_g_anisotropic(wi, self.alpha_x, self.alpha_y)
+            * smith_g_anisotropic(wo, self.alpha_x, self.alpha_y);
+        let specular = fs * (gtr2_anisotropic(h, self.alpha_x, self.alpha_y) * gs);
+
+        let fr = 0.04 + 0.96 * f_h;
+        let gr = smith_g(wi.z, 0.25) * smith_g(wo.z, 0.25);
+        let clearcoat = 0.25 * self.clearcoat * gtr1(h.z, CLEARCOAT_ALPHA) * fr * gr;
+
+        (diffuse + specular + Color::new(clearcoat, clearcoat, clearcoat))
+            * (1.0 - self.transmission)
+    }
+
+    /// Density of sampling `wi` from `wo` when the ray isn't transmitted.
+    fn reflection_pdf(&self, wo: Vec3, wi: Vec3) -> f64 {
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return 0.0;
+        }
+
+        let h = (wo + wi).normalize();
+        let jacobian = 4.0 * wo.dot(h).abs();
+        let (diffuse, specular, clearcoat) = self.lobe_probabilities();
+
+        diffuse * wi.z / PI
+            + specular * gtr2_anisotropic(h, self.alpha_x, self.alpha_y) * h.z / jacobian
+            + clearcoat * gtr1(h.z, CLEARCOAT_ALPHA) * h.z / jacobian
+    }
+}
+
+impl Principled {
+    fn shading(&self, intersection: &Intersection, ray: &Ray) -> Shading {
+        let value = |texture: &Texture| {
+            let color = texture.filtered_value(
+                intersection.u,
+                intersection.v,
+                intersection.p,
+                intersection.uv_width(),
+            );
+
+            ((color.r + color.g + color.b) / 3.0).clamp(0.0, 1.0)
+        };
+
+        let roughness = value(&self.roughness);
+        let aspect = (1.0 - 0.9 * value(&self.anisotropic)).sqrt();
+
+        // Shade the side the ray came from.
+        let mut normal = intersection.normal.normalize();
+
+        if ray.direction.dot(normal) > 0.0 {
+            normal = -normal;
+        }
+
+        let mut tangent = (intersection.dpdu - normal * normal.dot(intersection.dpdu)).normalize();
+
+        if !tangent.is_finite() {
+            let helper = if normal.x.abs() > 0.9 {
+                Vec3::new(0.0, 1.0, 0.0)
+            } else {
+                Vec3::new(1.0, 0.0, 0.0)
+            };
+
+            tangent = helper.cross(normal).normalize();
+        }
+
+        Shading {
+            base_color: self.base_color.filtered_value(
+                intersection.u,
+                intersection.v,
+                intersection.p,
+                intersection.uv_width(),
+            ),
+            metallic: value(&self.metallic),
+            roughness,
+            specular: value(&self.specular),
+            specular_tint: value(&self.specular_tint),
+            sheen: value(&self.sheen),
+            clearcoat: value(&self.clearcoat),
+            transmission: value(&self.transmission) * (1.0 - value(&self.metallic)),
+            alpha_x: (roughness * roughness / aspect).max(MIN_ALPHA),
+            alpha_y: (roughness * roughness * aspect).max(MIN_ALPHA),
+            bitangent: normal.cross(tangent),
+            tangent,
+            normal,
+        }
+    }
+
+    /// Refracts or reflects the ray like a dielectric, blurred by the
+    /// roughness.
+    fn transmit(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        shading: &Shading,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
+        let direction = ray.direction.normalize();
+        let entering = direction.dot(intersection.normal) < 0.0;
+        let ni_over_nt = if entering {
+            1.0 / self.refractive_index
+        } else {
+            self.refractive_index
+        };
+        let cosine = -direction.dot(shading.normal);
+
+        let reflected = super::reflect(direction, shading.normal);
+        let ideal = match super::refract(direction, shading.normal, ni_over_nt) {
+            Some(refracted)
+                if sampler.get_1d() >= super::schlick(cosine, self.refractive_index) =>
+            {
+                refracted
+            }
+            _ => reflected,
+        };
+
+        let fuzz = shading.roughness * shading.roughness;
+        let mut scattered = ideal.normalize() + fuzz * super::random_in_unit_sphere(sampler);
+
+        // Keep blurred directions on the same side of the surface.
+        if scattered.dot(shading.normal) * ideal.dot(shading.normal) <= 0.0 {
+            scattered = ideal;
+        }
+
+        Some(Scattered {
+            scattered: Ray::new(intersection.p, scattered),
+            attenuation: shading.base_color,
+            pdf: 0.0,
+            lobe: Lobe::Specular,
+        })
+    }
+}
+
+impl Scatterable for Principled {
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
+        let shading = self.shading(intersection, ray);
+
+        if sampler.get_1d() < shading.transmission {
+            return self.transmit(ray, intersection, &shading, sampler);
+        }
+
+        let wo = shading.to_local(-ray.direction.normalize());
+        let (diffuse, specular, _) = shading.lobe_probabilities();
+        let lobe = sampler.get_1d();
+        let (u, v) = sampler.get_2d();
+
+        let (wi, kind) = if lobe < diffuse {
+            let r = u.sqrt();
+            let phi = 2.0 * PI * v;
+            let wi = Vec3::new(r * phi.cos(), r * phi.sin(), (1.0 - u).max(0.0).sqrt());
+
+            (wi, Lobe::Diffuse)
+        } else {
+            let h = if lobe < diffuse + specular {
+                let phi = 2.0 * PI * v;
+                let slope = (u / (1.0 - u).max(1e-9)).sqrt();
+
+                Vec3::new(
+                    slope * shading.alpha_x * phi.cos(),
+                    slope * shading.alpha_y * phi.sin(),
+                    1.0,
+                )
+                .normalize()
+            } else {
+                let a2 = CLEARCOAT_ALPHA * CLEARCOAT_ALPHA;
+                let cos_theta = ((1.0 - a2.powf(1.0 - u)) / (1.0 - a2)).max(0.0).sqrt();
+                let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+                let phi = 2.0 * PI * v;
+
+                Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+            };
+
+            (h * (2.0 * wo.dot(h)) - wo, Lobe::Specular)
+        };
+
+        let pdf = shading.reflection_pdf(wo, wi) * (1.0 - shading.transmission);
+
+        if wi.z <= 0.0 || pdf <= 0.0 {
+            return None;
+        }
+
+        Some(Scattered {
+            scattered: Ray::new(intersection.p, shading.to_world(wi)),
+            attenuation: shading.brdf(wo, wi) * (wi.z / pdf),
+            pdf,
+            lobe: kind,
+        })
+    }
+
+    fn evaluate(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> Color {
+        let shading = self.shading(intersection, ray);
+        let wo = shading.to_local(-ray.direction.normalize());
+        let wi = shading.to_local(direction.normalize());
+
+        shading.brdf(wo, wi) * wi.z.max(0.0)
+    }
+
+    fn pdf(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> f64 {
+        let shading = self.shading(intersection, ray);
+        let wo = shading.to_local(-ray.direction.normalize());
+        let wi = shading.to_local(direction.normalize());
+
+        shading.reflection_pdf(wo, wi) * (1.0 - shading.transmission)
+    }
+}
+
+fn lerp(a: Color, b: Color, t: f64) -> Color {
+    a * (1.0 - t) + b * t
+}
+
+fn schlick_weight(cosine: f64) -> f64 {
+    (1.0 - cosine).clamp(0.0, 1.0).powi(5)
+}
+
+/// Generalized Trowbridge-Reitz distribution with γ = 1, of the clear coat.
+fn gtr1(cos_h: f64, alpha: f64) -> f64 {
+    let a2 = alpha * alpha;
+
+    (a2 - 1.0) / (PI * a2.ln() * (1.0 + (a2 - 1.0) * cos_h * cos_h))
+}
+
+/// Anisotropic GGX distribution of the half vector `h` in the shading frame.
+fn gtr2_anisotropic(h: Vec3, alpha_x: f64, alpha_y: f64) -> f64 {
+    let d = (h.x / alpha_x).powi(2) + (h.y / alpha_y).powi(2) + h.z * h.z;
+
+    1.0 / (PI * alpha_x * alpha_y * d * d)
+}
+
+/// Smith shadowing of the GGX distribution over `2 cos(w)`.
+fn smith_g(cos: f64, alpha: f64) -> f64 {
+    let a2 = alpha * alpha;
+    let c2 = cos * cos;
+
+    1.0 / (cos + (a2 + c2 - a2 * c2).sqrt())
+}
+
+/// Anisotropic Smith shadowing over `2 cos(w)`.
+fn smith_g_anisotropic(w: Vec3, alpha_x: f64, alpha_y: f64) -> f64 {
+    1.0 / (w.z + ((w.x * alpha_x).powi(2) + (w.y * alpha_y).powi(2) + w.z * w.z).sqrt())
+}
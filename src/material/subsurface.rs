@@ -0,0 +1,130 @@
+use super::{Lobe, Medium, Scatterable, Scattered};
+use crate::intersectable::Intersection;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::Color;
+use crate::Texture;
+use crate::Vec3;
+
+use std::f64::consts::PI;
+
+/// Translucent material, light goes through its surface and wanders around
+/// inside scattered by the medium it encloses until it finds its way out or
+/// is absorbed.
+///
+/// # Members
+/// * `albedo` - Color the material takes, the fraction of the light going in
+///   that eventually comes out. Read where the light goes in.
+/// * `mean_free_path` - Average distance light travels inside between
+///   scattering events, for each color.
+/// * `anisotropy` - Mean cosine of the angle light is scattered by inside,
+///   see `Medium`.
+/// * `refractive_index` - Refractive index of the inside of the surface,
+///   which reflects part of the light before it goes in.
+#[derive(Clone, Debug)]
+pub struct Subsurface {
+    pub albedo: Texture,
+    pub mean_free_path: Color,
+    pub anisotropy: f64,
+    pub refractive_index: f64,
+}
+
+impl Scatterable for Subsurface {
+    fn medium(&self, intersection: &Intersection) -> Option<Medium> {
+        let albedo = self.albedo.filtered_value(
+            intersection.u,
+            intersection.v,
+            intersection.p,
+            intersection.uv_width(),
+        );
+
+        // Light scattered many times is absorbed a bit every time, so the
+        // single scattering albedo is inverted from the albedo of the whole
+        // walk. From Chiang et al., "Practical and Controllable Subsurface
+        // Scattering for Production Path Tracing".
+        let single_scattering = |a: f64| {
+            let a = a.clamp(0.0, 1.0);
+
+            1.0 - (4.09712 + 4.20863 * a - (9.59217 + 41.6808 * a + 17.7126 * a * a).sqrt()).powi(2)
+        };
+        let coefficient = |distance: f64| distance.max(1e-6).recip();
+
+        let extinction = Color::new(
+            coefficient(self.mean_free_path.r),
+            coefficient(self.mean_free_path.g),
+            coefficient(self.mean_free_path.b),
+        );
+        let scattering = Color::new(
+            extinction.r * single_scattering(albedo.r),
+            extinction.g * single_scattering(albedo.g),
+            extinction.b * single_scattering(albedo.b),
+        );
+
+        Some(Medium {
+            absorption: Color::new(
+                extinction.r - scattering.r,
+                extinction.g - scattering.g,
+                extinction.b - scattering.b,
+            ),
+            scattering,
+            anisotropy: self.anisotropy.clamp(-0.99, 0.99),
+        })
+    }
+
+    fn sample(
+        &self,
+        ray: &Ray,
+        intersection: &Intersection,
+        sampler: &mut Sampler,
+    ) -> Option<Scattered> {
+        let normal = intersection.normal.normalize();
+        let cosine = -ray.direction.normalize().dot(normal);
+
+        // Light leaves through the surface spread around like off a matte
+        // surface, and goes in the same way unless it's reflected by it.
+        let (direction, lobe) = if cosine < 0.0 {
+            (diffuse_direction(normal, sampler), Lobe::Diffuse)
+        } else if sampler.get_1d() < super::schlick(cosine, self.refractive_index) {
+            (
+                super::reflect(ray.direction.normalize(), normal),
+                Lobe::Specular,
+            )
+        } else {
+            (diffuse_direction(-normal, sampler), Lobe::Diffuse)
+        };
+
+        Some(Scattered {
+            scattered: Ray::new(intersection.p, direction),
+            attenuation: Color::new(1.0, 1.0, 1.0),
+            pdf: self.pdf(ray, intersection, direction),
+            lobe,
+        })
+    }
+
+    fn evaluate(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> Color {
+        Color::new(1.0, 1.0, 1.0) * self.pdf(ray, intersection, direction)
+    }
+
+    /// Only the way out can be evaluated, light going in has nothing to
+    /// meet inside but the medium.
+    fn pdf(&self, ray: &Ray, intersection: &Intersection, direction: Vec3) -> f64 {
+        let normal = intersection.normal.normalize();
+
+        if ray.direction.dot(normal) <= 0.0 {
+            return 0.0;
+        }
+
+        normal.dot(direction.normalize()).max(0.0) / PI
+    }
+}
+
+/// Direction distributed by its cosine with `normal`.
+fn diffuse_direction(normal: Vec3, sampler: &mut Sampler) -> Vec3 {
+    let direction = normal + super::random_unit_vector(sampler);
+
+    if direction.norm() < 1e-12 {
+        normal
+    } else {
+        direction
+    }
+}
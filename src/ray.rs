@@ -1,12 +1,42 @@
 use super::Vec3;
 
+/// Half line from `origin` along `direction`.
+///
+/// # Members
+/// * `origin` - Point the ray starts at.
+/// * `direction` - Direction of the ray, not necessarily normalized.
+/// * `differential` - Neighbor rays of camera rays, see `RayDifferential`.
+/// * `wavelength` - Wavelength in nanometers of the light carried by the
+///   ray when rendering with `Integrator::Spectral`, for materials whose
+///   scattering depends on it. `None` when rendering colors.
 #[derive(Clone, Debug)]
 pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
+    pub differential: Option<RayDifferential>,
+    pub wavelength: Option<f64>,
+}
+
+/// Auxiliary rays offset by one pixel in screen x and y, used to estimate the
+/// footprint of a ray on the surfaces it hits.
+#[derive(Clone, Copy, Debug)]
+pub struct RayDifferential {
+    pub rx_origin: Vec3,
+    pub rx_direction: Vec3,
+    pub ry_origin: Vec3,
+    pub ry_direction: Vec3,
 }
 
 impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Ray {
+            origin,
+            direction,
+            differential: None,
+            wavelength: None,
+        }
+    }
+
     pub fn point_at(&self, t: f64) -> Vec3 {
         self.origin + t * self.direction
     }
@@ -1,24 +1,45 @@
+use crate::hash::Fnv1a;
+
 use rand::prelude::*;
 use rand_xoshiro::Xoshiro256PlusPlus;
 use std::borrow::BorrowMut;
 use std::cell::RefCell;
+use std::hash::Hasher;
 
 thread_local! {
-    static GENERATOR: RefCell<RandomNumber> = RefCell::new(RandomNumber::create())
+    static GENERATOR: RefCell<RandomNumber> = RefCell::new(RandomNumber::create(0))
 }
 
 pub fn get_random_number() -> f64 {
     GENERATOR.with(|g| g.borrow_mut().borrow_mut().generate())
 }
 
+/// Restarts the random numbers of the current thread from `seed`.
+pub fn seed(seed: u64) {
+    GENERATOR.with(|g| *g.borrow_mut() = RandomNumber::create(seed))
+}
+
+/// Seed of the random numbers used by a sample of a pixel, so every sample
+/// gets the same numbers no matter which thread takes it or when.
+pub fn sample_seed(seed: u64, x: u32, y: u32, sample: u32) -> u64 {
+    let mut hasher = Fnv1a::default();
+
+    hasher.write_u64(seed);
+    hasher.write_u32(x);
+    hasher.write_u32(y);
+    hasher.write_u32(sample);
+
+    hasher.finish()
+}
+
 pub struct RandomNumber {
     rng: Xoshiro256PlusPlus,
 }
 
 impl RandomNumber {
-    pub fn create() -> Self {
+    pub fn create(seed: u64) -> Self {
         RandomNumber {
-            rng: Xoshiro256PlusPlus::from_rng(thread_rng()).unwrap(),
+            rng: Xoshiro256PlusPlus::seed_from_u64(seed),
         }
     }
 
@@ -0,0 +1,118 @@
+//! Sample generation.
+//!
+//! Every random decision taken while tracing a sample (where in the pixel the
+//! camera ray goes, in which direction a material scatters, whether russian
+//! roulette ends a path) takes its numbers from a `Sampler`. Each decision
+//! uses a new dimension of the sample, and samplers other than `Independent`
+//! spread the values of each dimension evenly over the samples of a pixel so
+//! images converge faster than with independent random numbers.
+
+use crate::hash::{self, Fnv1a};
+
+use std::hash::Hasher;
+
+mod halton;
+mod independent;
+mod sobol;
+mod stratified;
+
+use halton::Halton;
+use independent::Independent;
+use sobol::Sobol;
+use stratified::Stratified;
+
+/// Sampler used by a render.
+///
+/// * `Independent` - Independent uniform random numbers.
+/// * `Stratified` - Jittered samples, each dimension is split in as many
+///   strata as samples per pixel and every sample falls in a different one.
+/// * `Halton` - Halton sequence with a random offset per pixel.
+/// * `Sobol` - Owen scrambled Sobol sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SamplerKind {
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+/// Source of the numbers a sample is computed from, see `SamplerKind`.
+#[derive(Clone, Debug)]
+pub enum Sampler {
+    Independent(Independent),
+    Stratified(Stratified),
+    Halton(Halton),
+    Sobol(Sobol),
+}
+
+pub trait SamplerObject {
+    /// Starts sample number `index` of the pixel at `x`, `y`, going back to
+    /// its first dimension.
+    fn start_sample(&mut self, x: u32, y: u32, index: u32);
+    fn get_1d(&mut self) -> f64;
+    fn get_2d(&mut self) -> (f64, f64);
+}
+
+impl Sampler {
+    /// Creates a sampler.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Which sampler to create.
+    /// * `seed` - Seed of the random numbers used by the sampler.
+    /// * `samples` - Samples that will be taken for each pixel at most.
+    pub fn new(kind: SamplerKind, seed: u64, samples: u32) -> Sampler {
+        match kind {
+            SamplerKind::Independent => Sampler::Independent(Independent),
+            SamplerKind::Stratified => Sampler::Stratified(Stratified::new(seed, samples)),
+            SamplerKind::Halton => Sampler::Halton(Halton::new(seed)),
+            SamplerKind::Sobol => Sampler::Sobol(Sobol::new(seed)),
+        }
+    }
+
+    pub fn start_sample(&mut self, x: u32, y: u32, index: u32) {
+        match self {
+            Sampler::Independent(sampler) => sampler.start_sample(x, y, index),
+            Sampler::Stratified(sampler) => sampler.start_sample(x, y, index),
+            Sampler::Halton(sampler) => sampler.start_sample(x, y, index),
+            Sampler::Sobol(sampler) => sampler.start_sample(x, y, index),
+        }
+    }
+
+    /// Next dimension of the current sample, a number in [0, 1).
+    pub fn get_1d(&mut self) -> f64 {
+        match self {
+            Sampler::Independent(sampler) => sampler.get_1d(),
+            Sampler::Stratified(sampler) => sampler.get_1d(),
+            Sampler::Halton(sampler) => sampler.get_1d(),
+            Sampler::Sobol(sampler) => sampler.get_1d(),
+        }
+    }
+
+    /// Next two dimensions of the current sample, a point in [0, 1)^2.
+    pub fn get_2d(&mut self) -> (f64, f64) {
+        match self {
+            Sampler::Independent(sampler) => sampler.get_2d(),
+            Sampler::Stratified(sampler) => sampler.get_2d(),
+            Sampler::Halton(sampler) => sampler.get_2d(),
+            Sampler::Sobol(sampler) => sampler.get_2d(),
+        }
+    }
+}
+
+/// Hash of a list of values, used to derive seeds for pixels and dimensions.
+fn mix(values: &[u64]) -> u64 {
+    let mut hasher = Fnv1a::default();
+
+    for value in values {
+        hasher.write_u64(*value);
+    }
+
+    // FNV mixes the low bits poorly.
+    hash::finalize(hasher.finish())
+}
+
+/// Converts the bits of a fixed point number in [0, 1) to a float.
+fn to_unit(bits: u32) -> f64 {
+    f64::from(bits) / 4_294_967_296.0
+}
@@ -0,0 +1,78 @@
+use super::SamplerObject;
+use crate::rng;
+
+/// Bases of the dimensions of the Halton sequence, dimensions past them fall
+/// back to independent random numbers.
+const PRIMES: [u32; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311,
+];
+
+/// Halton sequence, the radical inverse of the sample index in a different
+/// prime base for every dimension.
+///
+/// Every pixel uses the same points, rotated by a random offset per pixel and
+/// dimension (Cranley-Patterson rotation) so neighbor pixels don't repeat the
+/// same pattern.
+#[derive(Clone, Debug)]
+pub struct Halton {
+    seed: u64,
+    pixel_seed: u64,
+    index: u32,
+    dimension: usize,
+}
+
+impl Halton {
+    pub fn new(seed: u64) -> Self {
+        Halton {
+            seed,
+            pixel_seed: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+}
+
+impl SamplerObject for Halton {
+    fn start_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel_seed = super::mix(&[self.seed, u64::from(x), u64::from(y)]);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let dimension = self.dimension;
+        self.dimension += 1;
+
+        match PRIMES.get(dimension) {
+            Some(&base) => {
+                let offset =
+                    super::to_unit(super::mix(&[self.pixel_seed, dimension as u64]) as u32);
+
+                (radical_inverse(self.index, base) + offset).fract()
+            }
+            None => rng::get_random_number(),
+        }
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.get_1d(), self.get_1d())
+    }
+}
+
+/// Mirrors the digits of `index` in `base` around the decimal point.
+fn radical_inverse(mut index: u32, base: u32) -> f64 {
+    let inverse_base = f64::from(base).recip();
+    let mut factor = inverse_base;
+    let mut result = 0.0;
+
+    while index > 0 {
+        result += f64::from(index % base) * factor;
+        index /= base;
+        factor *= inverse_base;
+    }
+
+    result
+}
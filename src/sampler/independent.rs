@@ -0,0 +1,19 @@
+use super::SamplerObject;
+use crate::rng;
+
+/// Independent uniform random numbers from the random number generator of
+/// the thread, which the renderer seeds for every sample.
+#[derive(Clone, Debug, Default)]
+pub struct Independent;
+
+impl SamplerObject for Independent {
+    fn start_sample(&mut self, _x: u32, _y: u32, _index: u32) {}
+
+    fn get_1d(&mut self) -> f64 {
+        rng::get_random_number()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (rng::get_random_number(), rng::get_random_number())
+    }
+}
@@ -0,0 +1,94 @@
+use super::SamplerObject;
+
+/// Owen scrambled Sobol sequence.
+///
+/// Uses the first two dimensions of the Sobol sequence, which form a (0, 2)
+/// sequence, for every pair of dimensions of a sample. Each pair shuffles the
+/// order of the points and scrambles their coordinates with its own seed so
+/// pairs aren't correlated with each other, following Burley, "Practical
+/// Hash-based Owen Scrambling".
+#[derive(Clone, Debug)]
+pub struct Sobol {
+    seed: u64,
+    pixel_seed: u64,
+    index: u32,
+    dimension: u32,
+}
+
+impl Sobol {
+    pub fn new(seed: u64) -> Self {
+        Sobol {
+            seed,
+            pixel_seed: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn next_seeds(&mut self) -> (u32, u32, u32) {
+        let seed = super::mix(&[self.pixel_seed, u64::from(self.dimension)]);
+        self.dimension += 1;
+
+        (seed as u32, (seed >> 32) as u32, super::mix(&[seed]) as u32)
+    }
+}
+
+impl SamplerObject for Sobol {
+    fn start_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel_seed = super::mix(&[self.seed, u64::from(x), u64::from(y)]);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let (shuffle, scramble, _) = self.next_seeds();
+        let index = nested_uniform_scramble(self.index, shuffle);
+
+        super::to_unit(nested_uniform_scramble(index.reverse_bits(), scramble))
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let (shuffle, scramble_x, scramble_y) = self.next_seeds();
+        let index = nested_uniform_scramble(self.index, shuffle);
+
+        (
+            super::to_unit(nested_uniform_scramble(index.reverse_bits(), scramble_x)),
+            super::to_unit(nested_uniform_scramble(
+                sobol_second_dimension(index),
+                scramble_y,
+            )),
+        )
+    }
+}
+
+/// Second dimension of the Sobol sequence, as a 0.32 fixed point number.
+fn sobol_second_dimension(mut index: u32) -> u32 {
+    let mut result = 0;
+    let mut direction = 1 << 31;
+
+    while index != 0 {
+        if index & 1 != 0 {
+            result ^= direction;
+        }
+
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+
+    result
+}
+
+/// Owen scrambling of a 0.32 fixed point number: every bit is flipped or not
+/// depending on a hash of the bits above it.
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+    x
+}
@@ -0,0 +1,108 @@
+use super::SamplerObject;
+use crate::rng;
+
+/// Jittered stratified samples.
+///
+/// Each 1D dimension is split in `samples` strata and each 2D dimension in a
+/// grid of at least `samples` cells. Every sample of a pixel takes a different
+/// stratum, chosen by a random permutation per pixel and dimension, and a
+/// random position inside of it.
+#[derive(Clone, Debug)]
+pub struct Stratified {
+    seed: u64,
+    samples: u32,
+    columns: u32,
+    rows: u32,
+    pixel_seed: u64,
+    index: u32,
+    dimension: u32,
+}
+
+impl Stratified {
+    pub fn new(seed: u64, samples: u32) -> Self {
+        let samples = samples.max(1);
+        let columns = f64::from(samples).sqrt().ceil() as u32;
+        let rows = samples.div_ceil(columns);
+
+        Stratified {
+            seed,
+            samples,
+            columns,
+            rows,
+            pixel_seed: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn stratum(&mut self, count: u32) -> u32 {
+        let seed = super::mix(&[self.pixel_seed, u64::from(self.dimension)]) as u32;
+        self.dimension += 1;
+
+        permute(self.index % count, count, seed)
+    }
+}
+
+impl SamplerObject for Stratified {
+    fn start_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel_seed = super::mix(&[self.seed, u64::from(x), u64::from(y)]);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let stratum = self.stratum(self.samples);
+
+        (f64::from(stratum) + rng::get_random_number()) / f64::from(self.samples)
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let cell = self.stratum(self.columns * self.rows);
+        let column = cell % self.columns;
+        let row = cell / self.columns;
+
+        (
+            (f64::from(column) + rng::get_random_number()) / f64::from(self.columns),
+            (f64::from(row) + rng::get_random_number()) / f64::from(self.rows),
+        )
+    }
+}
+
+/// Element `i` of a random permutation of [0, `l`) picked by `p`.
+///
+/// From Kensler, "Correlated Multi-Jittered Sampling".
+fn permute(mut i: u32, l: u32, p: u32) -> u32 {
+    let mut w = l - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+
+        if i < l {
+            break;
+        }
+    }
+
+    i.wrapping_add(p) % l
+}
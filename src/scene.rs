@@ -1,3 +1,4 @@
+use super::hash;
 use super::Camera;
 use super::BVH;
 
@@ -6,3 +7,11 @@ pub struct Scene {
     pub camera: Camera,
     pub world: BVH,
 }
+
+impl Scene {
+    /// Hash identifying the camera and objects of the scene, used to check
+    /// that saved render state belongs to it.
+    pub fn fingerprint(&self) -> u64 {
+        hash::debug_hash(&self.camera).rotate_left(7) ^ self.world.fingerprint()
+    }
+}
@@ -0,0 +1,362 @@
+//! Render settings.
+
+use crate::aov::Aov;
+use crate::filter::Filter;
+use crate::integrator::Integrator;
+use crate::sampler::SamplerKind;
+use crate::tile::{Tile, TileOrder};
+use crate::Error;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Parameters of a render.
+///
+/// # Members
+/// * `width` - Width of the resulting image.
+/// * `height` - Height of the resulting image.
+/// * `samples` - Samples per pixel to take, the maximum per pixel when
+///   adaptive sampling is enabled. Every sample traces a single camera ray,
+///   earlier versions traced four per sample.
+/// * `max_depth` - Hard limit of ray bouncing for the scene.
+/// * `gamma` - Gamma value used for gamma correction of the final image.
+/// * `workers` - How many threads to use.
+/// * `seed` - Seed of the random numbers. The same scene and settings with the
+///   same seed always produce the same image, no matter the workers or the
+///   tile order.
+/// * `sampler` - How the random numbers of each sample are generated.
+/// * `filter` - Filter the pixels are reconstructed with from the samples.
+/// * `integrator` - How the color of each sample is computed, see
+///   `Integrator` for the ones meant to debug a scene.
+/// * `tile_size` - Width and height in pixels of the tiles the image is split
+///   into, each tile is rendered as a single job by a worker.
+/// * `tile_order` - Order in which tiles are handed to the workers.
+/// * `region` - Part of the image to render, `None` for all of it. Pixels
+///   outside of it take no samples, this is meant to split an image between
+///   several partial renders, see `render_partial`.
+/// * `adaptive` - Adaptive sampling parameters, `None` to take `samples` on
+///   every pixel.
+/// * `progressive` - Progressive rendering parameters, `None` to produce the
+///   image only once it's done.
+/// * `checkpoint` - Where and how often to save the render state so it can
+///   be resumed, `None` to not save it.
+/// * `aovs` - AOVs to render along with the image, see `render` for where
+///   they are saved.
+/// * `denoise` - Denoiser parameters, `None` to keep the image as rendered.
+#[derive(Clone, Debug)]
+pub struct RenderSettings {
+    pub width: u32,
+    pub height: u32,
+    pub samples: u32,
+    pub max_depth: u32,
+    pub gamma: f64,
+    pub workers: usize,
+    pub seed: u64,
+    pub sampler: SamplerKind,
+    pub filter: Filter,
+    pub integrator: Integrator,
+    pub tile_size: u32,
+    pub tile_order: TileOrder,
+    pub region: Option<Tile>,
+    pub adaptive: Option<AdaptiveSampling>,
+    pub progressive: Option<Progressive>,
+    pub checkpoint: Option<Checkpoint>,
+    pub aovs: Vec<Aov>,
+    pub denoise: Option<Denoise>,
+}
+
+/// Adaptive sampling parameters.
+///
+/// The image is rendered in passes of `min_samples` samples. After each pass
+/// pixels whose relative error (standard error of the mean luminance over the
+/// mean luminance) is under `threshold` stop taking samples.
+///
+/// # Members
+/// * `min_samples` - Samples every pixel takes before checking convergence,
+///   also the samples taken by each pass. At least 2.
+/// * `threshold` - Relative error under which a pixel is converged.
+/// * `sample_map` - Optional filename where an image with the samples taken by
+///   each pixel is saved.
+#[derive(Clone, Debug)]
+pub struct AdaptiveSampling {
+    pub min_samples: u32,
+    pub threshold: f64,
+    pub sample_map: Option<PathBuf>,
+}
+
+/// Progressive rendering parameters.
+///
+/// The whole image is rendered in passes of `pass_samples` samples and a
+/// snapshot of it is produced after each one. The render stops when every
+/// pixel took `samples` samples or when `time_limit` runs out, whatever
+/// happens first. The time limit is checked between passes, so the render
+/// can go over it by up to a pass.
+///
+/// # Members
+/// * `pass_samples` - Samples per pixel taken by each pass.
+/// * `time_limit` - Optional time budget for the render.
+#[derive(Clone, Debug)]
+pub struct Progressive {
+    pub pass_samples: u32,
+    pub time_limit: Option<Duration>,
+}
+
+/// Checkpoint parameters.
+///
+/// The accumulated samples of every pixel are saved to `path` between passes,
+/// at most once every `interval`, and once more when the render finishes.
+/// Renders that are neither progressive nor adaptive are done in passes so
+/// there is a chance to save them. The first pass takes a single sample per
+/// pixel, and passes double in size until each one takes a quarter of the
+/// interval or more.
+///
+/// # Members
+/// * `path` - Filename of the checkpoint.
+/// * `interval` - Minimum time between checkpoints.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    pub path: PathBuf,
+    pub interval: Duration,
+}
+
+/// Denoiser parameters.
+///
+/// The image is filtered several times, blending every pixel with the ones
+/// around it unless their color, albedo or normal are too different. The
+/// albedo and normal of the first hit are rendered along with the image to
+/// guide it. Progressive snapshots are denoised too.
+///
+/// # Members
+/// * `iterations` - Times the image is filtered, every iteration reaches
+///   twice as far as the previous one. Between 1 and 16.
+/// * `color_sigma` - How different, on lighting compressed to [0, 1), colors
+///   can be before they stop being blended.
+/// * `albedo_sigma` - How different albedos can be before they stop being
+///   blended.
+/// * `normal_sigma` - How different normals can be before they stop being
+///   blended.
+#[derive(Clone, Debug)]
+pub struct Denoise {
+    pub iterations: u32,
+    pub color_sigma: f64,
+    pub albedo_sigma: f64,
+    pub normal_sigma: f64,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            width: 640,
+            height: 480,
+            samples: 100,
+            max_depth: 50,
+            gamma: 2.2,
+            workers: 4,
+            seed: 0,
+            sampler: SamplerKind::Independent,
+            filter: Filter::Tent { radius: 1.0 },
+            integrator: Integrator::PathTracer,
+            tile_size: 32,
+            tile_order: TileOrder::Spiral,
+            region: None,
+            adaptive: None,
+            progressive: None,
+            checkpoint: None,
+            aovs: Vec::new(),
+            denoise: None,
+        }
+    }
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        AdaptiveSampling {
+            min_samples: 16,
+            threshold: 0.01,
+            sample_map: None,
+        }
+    }
+}
+
+impl Default for Progressive {
+    fn default() -> Self {
+        Progressive {
+            pass_samples: 1,
+            time_limit: None,
+        }
+    }
+}
+
+impl Default for Denoise {
+    fn default() -> Self {
+        Denoise {
+            iterations: 5,
+            color_sigma: 1.0,
+            albedo_sigma: 0.1,
+            normal_sigma: 0.3,
+        }
+    }
+}
+
+impl Denoise {
+    pub fn validate(&self) -> Result<(), Error> {
+        if !(1..=16).contains(&self.iterations) {
+            return Err(Error::InvalidSettings(
+                "denoise iterations must be between 1 and 16",
+            ));
+        }
+
+        for sigma in [self.color_sigma, self.albedo_sigma, self.normal_sigma] {
+            if !(sigma > 0.0 && sigma.is_finite()) {
+                return Err(Error::InvalidSettings("denoise sigmas must be positive"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl RenderSettings {
+    pub fn aspect_ratio(&self) -> f64 {
+        f64::from(self.width) / f64::from(self.height)
+    }
+
+    /// AOVs the film keeps: `aovs` followed by the albedo and normal when
+    /// denoising, if they aren't already there.
+    pub fn film_aovs(&self) -> Vec<Aov> {
+        let mut aovs = self.aovs.clone();
+
+        if self.denoise.is_some() {
+            for aov in [Aov::Albedo, Aov::Normal] {
+                if !aovs.contains(&aov) {
+                    aovs.push(aov);
+                }
+            }
+        }
+
+        aovs
+    }
+
+    /// The part of the image to render.
+    pub fn region(&self) -> Tile {
+        self.region.unwrap_or(Tile {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.width == 0 || self.height == 0 {
+            return Err(Error::InvalidSettings("image dimensions must be positive"));
+        }
+
+        if self.samples == 0 {
+            return Err(Error::InvalidSettings("samples must be positive"));
+        }
+
+        if self.workers == 0 {
+            return Err(Error::InvalidSettings("workers must be positive"));
+        }
+
+        if self.tile_size == 0 {
+            return Err(Error::InvalidSettings("tile size must be positive"));
+        }
+
+        let radius = self.filter.radius();
+
+        if !(radius >= 0.5 && radius.is_finite()) {
+            return Err(Error::InvalidSettings(
+                "filter radius must be at least half a pixel",
+            ));
+        }
+
+        if let Filter::Gaussian { sigma, .. } = self.filter {
+            if !(sigma > 0.0 && sigma.is_finite()) {
+                return Err(Error::InvalidSettings(
+                    "gaussian filter sigma must be positive",
+                ));
+            }
+        }
+
+        match self.integrator {
+            Integrator::Depth { max_distance }
+                if !(max_distance > 0.0 && max_distance.is_finite()) =>
+            {
+                return Err(Error::InvalidSettings(
+                    "depth integrator maximum distance must be positive",
+                ));
+            }
+            Integrator::AmbientOcclusion { distance }
+                if !(distance > 0.0 && distance.is_finite()) =>
+            {
+                return Err(Error::InvalidSettings(
+                    "ambient occlusion distance must be positive",
+                ));
+            }
+            Integrator::BvhHeatmap { max_tests: 0 } => {
+                return Err(Error::InvalidSettings(
+                    "BVH heatmap maximum tests must be positive",
+                ));
+            }
+            _ => {}
+        }
+
+        if let Some(region) = &self.region {
+            if region.width == 0
+                || region.height == 0
+                || region
+                    .x
+                    .checked_add(region.width)
+                    .is_none_or(|end| end > self.width)
+                || region
+                    .y
+                    .checked_add(region.height)
+                    .is_none_or(|end| end > self.height)
+            {
+                return Err(Error::InvalidSettings(
+                    "region must be a non empty part of the image",
+                ));
+            }
+        }
+
+        if !(self.gamma > 0.0 && self.gamma.is_finite()) {
+            return Err(Error::InvalidSettings("gamma must be positive"));
+        }
+
+        if let Some(adaptive) = &self.adaptive {
+            if adaptive.min_samples < 2 {
+                return Err(Error::InvalidSettings(
+                    "adaptive sampling needs at least 2 minimum samples",
+                ));
+            }
+
+            if adaptive.min_samples > self.samples {
+                return Err(Error::InvalidSettings(
+                    "adaptive minimum samples can't exceed samples",
+                ));
+            }
+
+            if !(adaptive.threshold > 0.0 && adaptive.threshold.is_finite()) {
+                return Err(Error::InvalidSettings(
+                    "adaptive threshold must be positive",
+                ));
+            }
+        }
+
+        if let Some(progressive) = &self.progressive {
+            if progressive.pass_samples == 0 {
+                return Err(Error::InvalidSettings(
+                    "progressive pass samples must be positive",
+                ));
+            }
+        }
+
+        if let Some(denoise) = &self.denoise {
+            denoise.validate()?;
+        }
+
+        Ok(())
+    }
+}
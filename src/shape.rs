@@ -1,5 +1,7 @@
 mod disc;
 mod sphere;
+mod validate;
 
 pub use disc::Disc;
 pub use sphere::Sphere;
+pub use validate::validate;
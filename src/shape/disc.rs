@@ -1,6 +1,7 @@
 use crate::aabb::AABB;
 use crate::intersectable::*;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 use crate::Material;
 use crate::Vec3;
 
@@ -14,7 +15,8 @@ pub struct Disc {
 
 impl Intersectable for Disc {
     fn bounding_box(&self) -> AABB {
-        let corner = Vec3::new(self.radius, self.radius, self.radius);
+        let radius = self.radius.abs();
+        let corner = Vec3::new(radius, radius, radius);
 
         AABB {
             min: self.center - corner,
@@ -22,10 +24,27 @@ impl Intersectable for Disc {
         }
     }
 
-    fn intersect(&self, ray: &Ray, min: f64, max: f64) -> Option<Intersection> {
+    fn material(&self) -> Option<&Material> {
+        Some(&self.material)
+    }
+
+    fn area(&self) -> f64 {
+        std::f64::consts::PI * self.radius * self.radius
+    }
+
+    fn sample_surface(&self, sampler: &mut Sampler) -> Option<Vec3> {
+        let (u, v) = sampler.get_2d();
+        let r = self.radius * u.sqrt();
+        let phi = 2.0 * std::f64::consts::PI * v;
+        let (tangent, bitangent) = tangent_vectors(self.normal);
+
+        Some(self.center + tangent * (r * phi.cos()) + bitangent * (r * phi.sin()))
+    }
+
+    fn intersect(&self, ray: &Ray, min: f64, max: f64) -> Option<Intersection<'_>> {
         let denom = self.normal.dot(ray.direction);
 
-        if denom.abs() > std::f64::EPSILON {
+        if denom.abs() > f64::EPSILON {
             let v = self.center - ray.origin;
 
             let distance = v.dot(self.normal) / denom;
@@ -34,13 +53,23 @@ impl Intersectable for Disc {
             let d = p - self.center;
 
             if d.norm() < self.radius * self.radius && distance < max && distance > min {
+                let (dpdu, dpdv) = tangent_vectors(self.normal);
+
                 Some(Intersection {
                     t: distance,
                     p: ray.point_at(distance),
                     u: 0.0,
                     v: 0.0,
                     normal: self.normal,
+                    dpdu: dpdu * self.radius,
+                    dpdv: dpdv * self.radius,
+                    dudx: 0.0,
+                    dvdx: 0.0,
+                    dudy: 0.0,
+                    dvdy: 0.0,
                     material: &self.material,
+                    object_id: 0,
+                    material_id: 0,
                 })
             } else {
                 None
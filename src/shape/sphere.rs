@@ -1,6 +1,7 @@
 use crate::aabb::AABB;
 use crate::intersectable::*;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 use crate::Material;
 use crate::Vec3;
 
@@ -13,7 +14,9 @@ pub struct Sphere {
 
 impl Intersectable for Sphere {
     fn bounding_box(&self) -> AABB {
-        let corner = Vec3::new(self.radius, self.radius, self.radius);
+        // A negative radius turns the normals inwards, for hollow spheres.
+        let radius = self.radius.abs();
+        let corner = Vec3::new(radius, radius, radius);
 
         AABB {
             min: self.center - corner,
@@ -21,7 +24,24 @@ impl Intersectable for Sphere {
         }
     }
 
-    fn intersect(&self, ray: &Ray, min: f64, max: f64) -> Option<Intersection> {
+    fn material(&self) -> Option<&Material> {
+        Some(&self.material)
+    }
+
+    fn area(&self) -> f64 {
+        4.0 * std::f64::consts::PI * self.radius * self.radius
+    }
+
+    fn sample_surface(&self, sampler: &mut Sampler) -> Option<Vec3> {
+        let (u, v) = sampler.get_2d();
+        let z = 1.0 - 2.0 * u;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * std::f64::consts::PI * v;
+
+        Some(self.center + Vec3::new(r * phi.cos(), r * phi.sin(), z) * self.radius)
+    }
+
+    fn intersect(&self, ray: &Ray, min: f64, max: f64) -> Option<Intersection<'_>> {
         let oc = ray.origin - self.center;
         let a = ray.direction.dot(ray.direction);
         let b = 2.0 * oc.dot(ray.direction);
@@ -47,6 +67,7 @@ impl Intersectable for Sphere {
             let p = ray.point_at(t);
             let normal = (p - self.center) / self.radius;
             let (u, v) = sphere_texture_uv(normal);
+            let (dpdu, dpdv) = sphere_partial_derivatives(normal, self.radius);
 
             Some(Intersection {
                 p,
@@ -54,7 +75,15 @@ impl Intersectable for Sphere {
                 u,
                 v,
                 normal,
+                dpdu,
+                dpdv,
+                dudx: 0.0,
+                dvdx: 0.0,
+                dudy: 0.0,
+                dvdy: 0.0,
                 material: &self.material,
+                object_id: 0,
+                material_id: 0,
             })
         } else {
             None
@@ -70,3 +99,19 @@ fn sphere_texture_uv(p: Vec3) -> (f64, f64) {
 
     (u, v)
 }
+
+/// Partial derivatives of the surface point with respect to the texture
+/// coordinates given by `sphere_texture_uv`.
+fn sphere_partial_derivatives(p: Vec3, radius: f64) -> (Vec3, Vec3) {
+    let phi = p.z.atan2(p.x);
+    let theta = p.y.asin();
+    let (sin_phi, cos_phi) = phi.sin_cos();
+    let (sin_theta, cos_theta) = theta.sin_cos();
+
+    let dpdu = Vec3::new(cos_theta * sin_phi, 0.0, -cos_theta * cos_phi)
+        * (2.0 * std::f64::consts::PI * radius);
+    let dpdv = Vec3::new(-sin_theta * cos_phi, cos_theta, -sin_theta * sin_phi)
+        * (std::f64::consts::PI * radius);
+
+    (dpdu, dpdv)
+}
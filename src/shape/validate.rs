@@ -0,0 +1,238 @@
+use crate::aabb::AABB;
+use crate::intersectable::*;
+use crate::ray::Ray;
+use crate::rng::RandomNumber;
+use crate::sampler::{Sampler, SamplerKind};
+use crate::Error;
+use crate::Vec3;
+
+/// Closest distance the renderer looks for hits at, to skip the surface a
+/// ray leaves from.
+const MIN_DISTANCE: f64 = 0.0001;
+
+/// Checks that `shape` keeps the invariants documented in `Intersectable` by
+/// shooting random rays at it, meant to be called from the tests of a shape.
+///
+/// Half of the rays come from around the bounding box aimed at a point in it,
+/// the other half start inside the box in a random direction. Their
+/// directions are not normalized. As many points are picked on the surface,
+/// for shapes that support `sample_surface`.
+///
+/// # Arguments
+///
+/// * `shape` - Shape to check.
+/// * `rays` - How many rays to shoot.
+/// * `seed` - Seed of the random rays.
+///
+/// # Errors
+/// Fails with `Error::InvalidShape` describing the first broken invariant
+/// found.
+pub fn validate(shape: &dyn Intersectable, rays: u32, seed: u64) -> Result<(), Error> {
+    let bounds = shape.bounding_box();
+
+    if !bounds.is_finite()
+        || bounds.min.x > bounds.max.x
+        || bounds.min.y > bounds.max.y
+        || bounds.min.z > bounds.max.z
+    {
+        return Err(Error::InvalidShape(
+            "bounding box must be finite with its minimum below its maximum",
+        ));
+    }
+
+    let center = (bounds.min + bounds.max) / 2.0;
+    let extent = bounds.max - bounds.min;
+    let size = extent.length().max(1e-3);
+    let tolerance = 1e-6 * (1.0 + size + center.length());
+
+    let mut rng = RandomNumber::create(seed);
+    let point_in_box = |rng: &mut RandomNumber| {
+        bounds.min + extent * Vec3::new(rng.generate(), rng.generate(), rng.generate())
+    };
+
+    for i in 0..rays {
+        let ray = if i % 2 == 0 {
+            let origin = center + random_direction(&mut rng) * size;
+            let target = point_in_box(&mut rng);
+
+            Ray::new(origin, target - origin)
+        } else {
+            let origin = point_in_box(&mut rng);
+            let scale = 0.5 + 1.5 * rng.generate();
+
+            Ray::new(origin, random_direction(&mut rng) * scale)
+        };
+
+        let hit = match shape.intersect(&ray, MIN_DISTANCE, f64::INFINITY) {
+            Some(hit) => hit,
+            None => continue,
+        };
+
+        check_hit(&hit, &ray, MIN_DISTANCE, f64::INFINITY, &bounds, tolerance)?;
+
+        // Measured along the direction, so the slack is too.
+        let slack = tolerance / ray.direction.length();
+
+        if shape.intersect(&ray, MIN_DISTANCE, hit.t - slack).is_some() {
+            return Err(Error::InvalidShape(
+                "intersect must return the nearest hit in the range",
+            ));
+        }
+
+        match shape.intersect(&ray, MIN_DISTANCE, hit.t + slack) {
+            Some(again) if (again.t - hit.t).abs() <= slack => {}
+            _ => {
+                return Err(Error::InvalidShape(
+                    "intersect must find the same hit in a narrower range",
+                ))
+            }
+        }
+
+        if let Some(further) = shape.intersect(&ray, hit.t + slack, f64::INFINITY) {
+            check_hit(
+                &further,
+                &ray,
+                hit.t + slack,
+                f64::INFINITY,
+                &bounds,
+                tolerance,
+            )?;
+        }
+
+        let doubled = Ray::new(ray.origin, ray.direction * 2.0);
+
+        match shape.intersect(&doubled, MIN_DISTANCE / 2.0, f64::INFINITY) {
+            Some(half) if (half.t * 2.0 - hit.t).abs() <= slack => {}
+            _ => {
+                return Err(Error::InvalidShape(
+                    "distance must be measured in multiples of the ray direction",
+                ))
+            }
+        }
+    }
+
+    check_surface_samples(shape, rays, seed, &bounds, size, tolerance)
+}
+
+/// Checks that the points picked by `sample_surface` are on the shape.
+fn check_surface_samples(
+    shape: &dyn Intersectable,
+    samples: u32,
+    seed: u64,
+    bounds: &AABB,
+    size: f64,
+    tolerance: f64,
+) -> Result<(), Error> {
+    let area = shape.area();
+
+    if !(area >= 0.0 && area.is_finite()) {
+        return Err(Error::InvalidShape("area must be finite and not negative"));
+    }
+
+    if area == 0.0 {
+        return Ok(());
+    }
+
+    let mut rng = RandomNumber::create(seed);
+    let mut sampler = Sampler::new(SamplerKind::Independent, seed, samples);
+
+    for i in 0..samples {
+        sampler.start_sample(0, 0, i);
+
+        let point = match shape.sample_surface(&mut sampler) {
+            Some(point) => point,
+            None => {
+                return Err(Error::InvalidShape(
+                    "shapes with an area must support sample_surface",
+                ))
+            }
+        };
+
+        // A ray from outside the bounding box must reach the point, or hit
+        // the shape before it.
+        let origin = point + random_direction(&mut rng) * (2.0 * size);
+        let ray = Ray::new(origin, point - origin);
+        let slack = tolerance / ray.direction.length();
+
+        match shape.intersect(&ray, MIN_DISTANCE / (2.0 * size), 1.0 + slack) {
+            Some(hit) => check_hit(&hit, &ray, 0.0, 1.0 + slack, bounds, tolerance)?,
+            None => {
+                return Err(Error::InvalidShape(
+                    "sample_surface must pick points on the surface",
+                ))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_hit(
+    hit: &Intersection,
+    ray: &Ray,
+    min: f64,
+    max: f64,
+    bounds: &AABB,
+    tolerance: f64,
+) -> Result<(), Error> {
+    if !(hit.t.is_finite() && hit.t > min && hit.t < max) {
+        return Err(Error::InvalidShape(
+            "intersection distance must be within the range",
+        ));
+    }
+
+    let tolerance = tolerance * (1.0 + hit.t * ray.direction.length());
+
+    if !hit.p.is_finite() || (hit.p - ray.point_at(hit.t)).length() > tolerance {
+        return Err(Error::InvalidShape(
+            "intersection point must be on the ray at its distance",
+        ));
+    }
+
+    let p = hit.p;
+
+    if p.x < bounds.min.x - tolerance
+        || p.y < bounds.min.y - tolerance
+        || p.z < bounds.min.z - tolerance
+        || p.x > bounds.max.x + tolerance
+        || p.y > bounds.max.y + tolerance
+        || p.z > bounds.max.z + tolerance
+    {
+        return Err(Error::InvalidShape(
+            "intersection point must be inside the bounding box",
+        ));
+    }
+
+    if !hit.normal.is_finite() || (hit.normal.length() - 1.0).abs() > 1e-6 {
+        return Err(Error::InvalidShape("normal must be normalized"));
+    }
+
+    if !(hit.u.is_finite() && hit.v.is_finite() && hit.dpdu.is_finite() && hit.dpdv.is_finite()) {
+        return Err(Error::InvalidShape(
+            "texture coordinates and their derivatives must be finite",
+        ));
+    }
+
+    if hit.dudx != 0.0
+        || hit.dvdx != 0.0
+        || hit.dudy != 0.0
+        || hit.dvdy != 0.0
+        || hit.object_id != 0
+        || hit.material_id != 0
+    {
+        return Err(Error::InvalidShape(
+            "differentials, object id and material id must be left at zero",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Uniformly distributed direction.
+fn random_direction(rng: &mut RandomNumber) -> Vec3 {
+    let z = 1.0 - 2.0 * rng.generate();
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * std::f64::consts::PI * rng.generate();
+
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
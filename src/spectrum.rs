@@ -0,0 +1,220 @@
+//! Spectral quantities, to turn light given by wavelength into colors.
+
+use crate::Color;
+
+use std::sync::OnceLock;
+
+/// Shortest wavelength of visible light in nanometers.
+pub const MIN_WAVELENGTH: f64 = 360.0;
+
+/// Longest wavelength of visible light in nanometers.
+pub const MAX_WAVELENGTH: f64 = 830.0;
+
+/// CIE 1931 color matching functions at `wavelength` nanometers, the X, Y
+/// and Z responses of the standard observer.
+///
+/// From the multi-lobe fit of Wyman et al., "Simple Analytic Approximations
+/// to the CIE XYZ Color Matching Functions".
+pub fn cie_xyz(wavelength: f64) -> (f64, f64, f64) {
+    let lobe = |mean: f64, below: f64, above: f64| {
+        let sigma = if wavelength < mean { below } else { above };
+        let t = (wavelength - mean) / sigma;
+
+        (-0.5 * t * t).exp()
+    };
+
+    let x = 1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+        - 0.065 * lobe(501.1, 20.4, 26.2);
+    let y = 0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1);
+    let z = 1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8);
+
+    (x, y, z)
+}
+
+/// Linear sRGB color, the color space of the renderer, of a CIE XYZ color.
+pub fn xyz_to_rgb(x: f64, y: f64, z: f64) -> Color {
+    Color::new(
+        3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+        -0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z,
+        0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+    )
+}
+
+/// Radiance emitted by a blackbody at `temperature` Kelvin at `wavelength`
+/// nanometers, up to a constant factor.
+pub fn planck(wavelength: f64, temperature: f64) -> f64 {
+    // Second radiation constant, h * c / k, in nanometers Kelvin.
+    const C2: f64 = 1.438_776_9e7;
+
+    let wavelength_um = wavelength * 1e-3;
+
+    wavelength_um.powi(-5) / ((C2 / (wavelength * temperature)).exp() - 1.0)
+}
+
+/// Reflectance at `wavelength` nanometers of a smooth spectrum with `color`,
+/// a linear sRGB color.
+///
+/// From Smits, "An RGB to Spectrum Conversion for Reflectances": the
+/// spectrum is built from white plus the spectra of the secondary and
+/// primary colors, white staying a constant spectrum of one.
+pub fn rgb_to_spectrum(color: Color, wavelength: f64) -> f64 {
+    let (r, g, b) = (color.r, color.g, color.b);
+    let at = |basis: &[f64; SMITS_BINS]| smits_basis(basis, wavelength);
+
+    if r <= g && r <= b {
+        r * at(&SMITS_WHITE)
+            + if g <= b {
+                (g - r) * at(&SMITS_CYAN) + (b - g) * at(&SMITS_BLUE)
+            } else {
+                (b - r) * at(&SMITS_CYAN) + (g - b) * at(&SMITS_GREEN)
+            }
+    } else if g <= r && g <= b {
+        g * at(&SMITS_WHITE)
+            + if r <= b {
+                (r - g) * at(&SMITS_MAGENTA) + (b - r) * at(&SMITS_BLUE)
+            } else {
+                (b - g) * at(&SMITS_MAGENTA) + (r - b) * at(&SMITS_RED)
+            }
+    } else {
+        b * at(&SMITS_WHITE)
+            + if r <= g {
+                (r - b) * at(&SMITS_YELLOW) + (g - r) * at(&SMITS_GREEN)
+            } else {
+                (g - b) * at(&SMITS_YELLOW) + (r - g) * at(&SMITS_RED)
+            }
+    }
+}
+
+/// Wavelengths carried by a path of the spectral integrator.
+///
+/// The first one, the hero wavelength, is picked at random and the others
+/// are spread evenly from it, so every path estimates the whole spectrum a
+/// bit. They're picked more often where the eye is more sensitive and stored
+/// in this order in the channels of the colors along the path.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Wavelengths([f64; 3]);
+
+impl Wavelengths {
+    /// Wavelengths for the random number `u` in [0, 1).
+    pub fn sample(u: f64) -> Self {
+        let wavelength = |offset: f64| sample_visible((u + offset).fract());
+
+        Wavelengths([
+            wavelength(0.0),
+            wavelength(1.0 / 3.0),
+            wavelength(2.0 / 3.0),
+        ])
+    }
+
+    /// The wavelength the path follows when its direction depends on it.
+    pub fn hero(&self) -> f64 {
+        self.0[0]
+    }
+
+    /// Values at each wavelength of the spectrum of `color`, see
+    /// `rgb_to_spectrum`.
+    pub fn upsample(&self, color: Color) -> Color {
+        Color::new(
+            rgb_to_spectrum(color, self.0[0]),
+            rgb_to_spectrum(color, self.0[1]),
+            rgb_to_spectrum(color, self.0[2]),
+        )
+    }
+
+    /// Linear sRGB color estimated from the `values` of a spectrum at each
+    /// wavelength. A constant spectrum of one is white.
+    pub fn color(&self, values: Color) -> Color {
+        let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+
+        for (wavelength, value) in self.0.iter().zip([values.r, values.g, values.b]) {
+            let (cx, cy, cz) = cie_xyz(*wavelength);
+            let weight = value / (3.0 * visible_pdf(*wavelength));
+
+            x += cx * weight;
+            y += cy * weight;
+            z += cz * weight;
+        }
+
+        let color = xyz_to_rgb(x, y, z);
+        let white = equal_energy_white();
+
+        Color::new(color.r / white.r, color.g / white.g, color.b / white.b)
+    }
+}
+
+/// Wavelength for the random number `u` in [0, 1), picked roughly following
+/// the sensitivity of the eye.
+///
+/// From Radziszewski et al., "An Improved Technique for Full Spectral
+/// Rendering", as fitted to the visible range by pbrt.
+fn sample_visible(u: f64) -> f64 {
+    (538.0 - 138.888_889 * (0.856_910_62 - 1.827_501_97 * u).atanh())
+        .clamp(MIN_WAVELENGTH, MAX_WAVELENGTH)
+}
+
+/// Probability density of `sample_visible` picking `wavelength`.
+fn visible_pdf(wavelength: f64) -> f64 {
+    if !(MIN_WAVELENGTH..=MAX_WAVELENGTH).contains(&wavelength) {
+        return 0.0;
+    }
+
+    0.003_939_804_2 / (0.0072 * (wavelength - 538.0)).cosh().powi(2)
+}
+
+/// Linear sRGB color of a constant spectrum of one over the visible range.
+fn equal_energy_white() -> Color {
+    static WHITE: OnceLock<Color> = OnceLock::new();
+
+    *WHITE.get_or_init(|| {
+        let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+        let mut wavelength = MIN_WAVELENGTH + 0.5;
+
+        while wavelength < MAX_WAVELENGTH {
+            let (cx, cy, cz) = cie_xyz(wavelength);
+
+            x += cx;
+            y += cy;
+            z += cz;
+            wavelength += 1.0;
+        }
+
+        xyz_to_rgb(x, y, z)
+    })
+}
+
+/// Value at `wavelength` of one of Smits' spectra, interpolated between the
+/// centers of its bins.
+fn smits_basis(basis: &[f64; SMITS_BINS], wavelength: f64) -> f64 {
+    let width = (SMITS_MAX - SMITS_MIN) / SMITS_BINS as f64;
+    let x = ((wavelength - SMITS_MIN) / width - 0.5).clamp(0.0, (SMITS_BINS - 1) as f64);
+    let i = (x as usize).min(SMITS_BINS - 2);
+    let t = x - i as f64;
+
+    basis[i] * (1.0 - t) + basis[i + 1] * t
+}
+
+const SMITS_BINS: usize = 10;
+const SMITS_MIN: f64 = 380.0;
+const SMITS_MAX: f64 = 720.0;
+
+const SMITS_WHITE: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const SMITS_CYAN: [f64; SMITS_BINS] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const SMITS_MAGENTA: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const SMITS_YELLOW: [f64; SMITS_BINS] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const SMITS_RED: [f64; SMITS_BINS] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f64; SMITS_BINS] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const SMITS_BLUE: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
@@ -1,31 +1,84 @@
 use crate::Color;
+use crate::Error;
 use crate::Vec3;
 
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::Arc;
+
 mod bitmap;
+mod cache;
+mod channel;
 mod checker;
 mod constant_color;
+mod invert;
+mod mipmap;
+mod mix;
+mod product;
+mod ramp;
+mod sum;
 
 use bitmap::Bitmap;
+pub use cache::TextureCache;
+use channel::Channel;
+pub use channel::ColorChannel;
 use checker::Checker;
 use constant_color::ConstantColor;
+use invert::Invert;
+use mipmap::MipMap;
+use mix::Mix;
+use product::Product;
+use ramp::Ramp;
+use sum::Sum;
 
 /// Texture object.
+///
+/// Textures can be combined into new ones with `mix`, `sum`, `product`,
+/// `ramp`, `invert` and `channel`. Where a texture is used as a number, like
+/// a mix factor, the average of its channels is taken.
+///
+/// Textures are cheap to clone, a bitmap shares its decoded image and mipmaps
+/// with its clones. Load bitmaps through a `TextureCache` to share them
+/// between textures too.
 #[derive(Clone, Debug)]
 pub enum Texture {
     Bitmap(Bitmap),
     Checker(Checker),
     ConstantColor(ConstantColor),
+    Mix(Mix),
+    Sum(Sum),
+    Product(Product),
+    Ramp(Ramp),
+    Invert(Invert),
+    Channel(Channel),
+    Custom(Arc<dyn TextureObject>),
 }
 
-pub trait TextureObject {
+/// Color of a surface as a function of its texture coordinates `u`, `v` and
+/// the hit point `p` in world space.
+///
+/// Implement it to add a texture to the path tracer, see `Texture::custom`.
+/// `Scene::fingerprint` hashes the `Debug` output of textures, so it has to
+/// cover everything that changes how the texture looks, the way bitmaps
+/// print a hash of their texels.
+pub trait TextureObject: Debug + Send + Sync {
     fn value(&self, u: f64, v: f64, p: Vec3) -> Color;
+
+    /// Average value of the texture over an area `width` wide in texture
+    /// space centered at `u`, `v`. Textures that don't alias can rely on the
+    /// point sampled `value`.
+    fn filtered_value(&self, u: f64, v: f64, p: Vec3, _width: f64) -> Color {
+        self.value(u, v, p)
+    }
 }
 
 impl Texture {
-    pub fn bitmap(path: &str) -> Texture {
-        Texture::Bitmap(Bitmap {
-            bitmap: image::open(path).unwrap(),
-        })
+    /// Loads a bitmap texture from an image file. Use a `TextureCache` to
+    /// avoid decoding the same file more than once.
+    pub fn bitmap<P: AsRef<Path>>(path: P) -> Result<Texture, Error> {
+        Ok(Texture::Bitmap(Bitmap {
+            mipmap: Arc::new(MipMap::load(path.as_ref())?),
+        }))
     }
 
     pub fn checker(squares: usize, odd: Color, even: Color) -> Texture {
@@ -36,11 +89,102 @@ impl Texture {
         Texture::ConstantColor(ConstantColor { color })
     }
 
+    /// Blends `a` into `b` by a constant `factor` between 0.0 and 1.0.
+    pub fn mix(a: Texture, b: Texture, factor: f64) -> Texture {
+        Texture::mix_by(
+            a,
+            b,
+            Texture::constant_color(Color::new(factor, factor, factor)),
+        )
+    }
+
+    /// Blends `a` into `b` by the value of `mask` at each point, all `a`
+    /// where it's 0.0 and all `b` where it's 1.0.
+    pub fn mix_by(a: Texture, b: Texture, mask: Texture) -> Texture {
+        Texture::Mix(Mix {
+            a: Box::new(a),
+            b: Box::new(b),
+            factor: Box::new(mask),
+        })
+    }
+
+    pub fn sum(a: Texture, b: Texture) -> Texture {
+        Texture::Sum(Sum {
+            a: Box::new(a),
+            b: Box::new(b),
+        })
+    }
+
+    pub fn product(a: Texture, b: Texture) -> Texture {
+        Texture::Product(Product {
+            a: Box::new(a),
+            b: Box::new(b),
+        })
+    }
+
+    /// Maps the value of `input` to colors. `stops` are positions and the
+    /// colors at them, colors between stops are interpolated linearly.
+    pub fn ramp(input: Texture, mut stops: Vec<(f64, Color)>) -> Texture {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Texture::Ramp(Ramp {
+            input: Box::new(input),
+            stops,
+        })
+    }
+
+    pub fn invert(texture: Texture) -> Texture {
+        Texture::Invert(Invert {
+            texture: Box::new(texture),
+        })
+    }
+
+    /// Gray texture with the value of `channel` of `texture`.
+    pub fn channel(texture: Texture, channel: ColorChannel) -> Texture {
+        Texture::Channel(Channel {
+            texture: Box::new(texture),
+            channel,
+        })
+    }
+
+    /// A texture defined outside of the path tracer, it can be combined with
+    /// the built-in ones like any other.
+    pub fn custom(texture: Arc<dyn TextureObject>) -> Texture {
+        Texture::Custom(texture)
+    }
+
     pub fn value(&self, u: f64, v: f64, p: Vec3) -> Color {
         match self {
             Texture::Bitmap(bitmap) => bitmap.value(u, v, p),
             Texture::Checker(checker) => checker.value(u, v, p),
             Texture::ConstantColor(color) => color.value(u, v, p),
+            Texture::Mix(mix) => mix.value(u, v, p),
+            Texture::Sum(sum) => sum.value(u, v, p),
+            Texture::Product(product) => product.value(u, v, p),
+            Texture::Ramp(ramp) => ramp.value(u, v, p),
+            Texture::Invert(invert) => invert.value(u, v, p),
+            Texture::Channel(channel) => channel.value(u, v, p),
+            Texture::Custom(custom) => custom.value(u, v, p),
+        }
+    }
+
+    pub fn filtered_value(&self, u: f64, v: f64, p: Vec3, width: f64) -> Color {
+        match self {
+            Texture::Bitmap(bitmap) => bitmap.filtered_value(u, v, p, width),
+            Texture::Checker(checker) => checker.filtered_value(u, v, p, width),
+            Texture::ConstantColor(color) => color.filtered_value(u, v, p, width),
+            Texture::Mix(mix) => mix.filtered_value(u, v, p, width),
+            Texture::Sum(sum) => sum.filtered_value(u, v, p, width),
+            Texture::Product(product) => product.filtered_value(u, v, p, width),
+            Texture::Ramp(ramp) => ramp.filtered_value(u, v, p, width),
+            Texture::Invert(invert) => invert.filtered_value(u, v, p, width),
+            Texture::Channel(channel) => channel.filtered_value(u, v, p, width),
+            Texture::Custom(custom) => custom.filtered_value(u, v, p, width),
         }
     }
 }
+
+/// Value of a texture used as a number, the average of its channels.
+fn scalar(color: Color) -> f64 {
+    (color.r + color.g + color.b) / 3.0
+}
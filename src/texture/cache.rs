@@ -0,0 +1,47 @@
+use super::bitmap::Bitmap;
+use super::mipmap::MipMap;
+use super::Texture;
+use crate::Error;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Keeps track of the bitmaps already loaded so materials using the same
+/// file share a single decoded copy.
+#[derive(Debug, Default)]
+pub struct TextureCache {
+    bitmaps: HashMap<PathBuf, Arc<MipMap>>,
+}
+
+impl TextureCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a bitmap texture for `path`, loading it only the first time it
+    /// is requested.
+    pub fn bitmap<P: AsRef<Path>>(&mut self, path: P) -> Result<Texture, Error> {
+        let path = path.as_ref();
+
+        let mipmap = match self.bitmaps.get(path) {
+            Some(mipmap) => Arc::clone(mipmap),
+            None => {
+                let mipmap = Arc::new(MipMap::load(path)?);
+                self.bitmaps.insert(path.to_path_buf(), Arc::clone(&mipmap));
+                mipmap
+            }
+        };
+
+        Ok(Texture::Bitmap(Bitmap { mipmap }))
+    }
+
+    /// Number of distinct bitmaps loaded.
+    pub fn len(&self) -> usize {
+        self.bitmaps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bitmaps.is_empty()
+    }
+}
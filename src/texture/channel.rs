@@ -0,0 +1,41 @@
+use super::{Texture, TextureObject};
+use crate::Color;
+use crate::Vec3;
+
+/// A channel of a color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChannel {
+    Red,
+    Green,
+    Blue,
+}
+
+/// Gray texture with the value of a single channel of another texture, to use
+/// the channels of an image as separate masks.
+#[derive(Clone, Debug)]
+pub struct Channel {
+    pub texture: Box<Texture>,
+    pub channel: ColorChannel,
+}
+
+impl Channel {
+    fn extract(&self, color: Color) -> Color {
+        let value = match self.channel {
+            ColorChannel::Red => color.r,
+            ColorChannel::Green => color.g,
+            ColorChannel::Blue => color.b,
+        };
+
+        Color::new(value, value, value)
+    }
+}
+
+impl TextureObject for Channel {
+    fn value(&self, u: f64, v: f64, p: Vec3) -> Color {
+        self.extract(self.texture.value(u, v, p))
+    }
+
+    fn filtered_value(&self, u: f64, v: f64, p: Vec3, width: f64) -> Color {
+        self.extract(self.texture.filtered_value(u, v, p, width))
+    }
+}
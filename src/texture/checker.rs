@@ -22,5 +22,5 @@ impl TextureObject for Checker {
 }
 
 fn is_even(v: u32) -> bool {
-    v % 2 == 0
+    v.is_multiple_of(2)
 }
@@ -0,0 +1,23 @@
+use super::{Texture, TextureObject};
+use crate::Color;
+use crate::Vec3;
+
+/// One minus a texture, channel by channel.
+#[derive(Clone, Debug)]
+pub struct Invert {
+    pub texture: Box<Texture>,
+}
+
+fn invert(color: Color) -> Color {
+    Color::new(1.0 - color.r, 1.0 - color.g, 1.0 - color.b)
+}
+
+impl TextureObject for Invert {
+    fn value(&self, u: f64, v: f64, p: Vec3) -> Color {
+        invert(self.texture.value(u, v, p))
+    }
+
+    fn filtered_value(&self, u: f64, v: f64, p: Vec3, width: f64) -> Color {
+        invert(self.texture.filtered_value(u, v, p, width))
+    }
+}
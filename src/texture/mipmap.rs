@@ -0,0 +1,254 @@
+//! Image pyramid used to filter bitmap textures.
+
+use crate::hash::Fnv1a;
+use crate::Color;
+use crate::Error;
+
+use image::error::{ImageError, ParameterError, ParameterErrorKind};
+use image::{DynamicImage, GenericImageView};
+
+use std::hash::Hasher;
+use std::path::Path;
+
+/// Chain of successively half sized copies of an image, from the full
+/// resolution image at level 0 down to a single texel.
+#[derive(Clone)]
+pub struct MipMap {
+    levels: Vec<MipLevel>,
+    hash: u64,
+}
+
+#[derive(Clone)]
+struct MipLevel {
+    width: u32,
+    height: u32,
+    texels: Vec<Color>,
+}
+
+impl MipMap {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let image = image::open(path).map_err(|source| Error::TextureLoad {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        // An empty image would never get down to a single texel.
+        if image.width() == 0 || image.height() == 0 {
+            return Err(Error::TextureLoad {
+                path: path.to_path_buf(),
+                source: ImageError::Parameter(ParameterError::from_kind(
+                    ParameterErrorKind::Generic("the image is empty".to_string()),
+                )),
+            });
+        }
+
+        Ok(Self::new(&image))
+    }
+
+    /// Builds the chain for `image`, which must not be empty.
+    pub fn new(image: &DynamicImage) -> Self {
+        let (width, height) = image.dimensions();
+        let rgb = image.to_rgb8();
+
+        let mut hasher = Fnv1a::default();
+        hasher.write(rgb.as_raw());
+
+        let texels = rgb
+            .pixels()
+            .map(|pixel| Color::from_u8(pixel[0], pixel[1], pixel[2]))
+            .collect();
+
+        let mut levels = vec![MipLevel {
+            width,
+            height,
+            texels,
+        }];
+
+        while let Some(level) = levels.last().and_then(MipLevel::downsample) {
+            levels.push(level);
+        }
+
+        MipMap {
+            levels,
+            hash: hasher.finish(),
+        }
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.levels[0].width, self.levels[0].height)
+    }
+
+    /// Hash of the full resolution texels, two images with the same size but
+    /// different contents have different hashes.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Trilinear lookup of the texture over an area `width` wide in texture
+    /// space.
+    ///
+    /// The level is picked so the area covers about a texel of it along the
+    /// longest side of the image, every level halving the texels across.
+    pub fn lookup(&self, u: f64, v: f64, width: f64) -> Color {
+        let last = (self.levels.len() - 1) as f64;
+        let (level_width, level_height) = self.dimensions();
+        let texels = width * f64::from(level_width.max(level_height));
+        let level = texels.max(1e-8).log2();
+
+        if level <= 0.0 {
+            self.levels[0].bilinear(u, v)
+        } else if level >= last {
+            self.levels[self.levels.len() - 1].bilinear(u, v)
+        } else {
+            let index = level.floor() as usize;
+            let delta = level - level.floor();
+
+            self.levels[index].bilinear(u, v) * (1.0 - delta)
+                + self.levels[index + 1].bilinear(u, v) * delta
+        }
+    }
+}
+
+impl MipLevel {
+    fn downsample(&self) -> Option<MipLevel> {
+        if self.width == 1 && self.height == 1 {
+            return None;
+        }
+
+        let width = self.width.div_ceil(2);
+        let height = self.height.div_ceil(2);
+        let mut texels = Vec::with_capacity(width as usize * height as usize);
+
+        for j in 0..height {
+            for i in 0..width {
+                let (x, y) = (2 * i as i64, 2 * j as i64);
+
+                texels.push(
+                    (self.texel(x, y)
+                        + self.texel(x + 1, y)
+                        + self.texel(x, y + 1)
+                        + self.texel(x + 1, y + 1))
+                        * 0.25,
+                );
+            }
+        }
+
+        Some(MipLevel {
+            width,
+            height,
+            texels,
+        })
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Color {
+        let x = x.clamp(0, i64::from(self.width) - 1) as u32;
+        let y = y.clamp(0, i64::from(self.height) - 1) as u32;
+
+        self.texels[y as usize * self.width as usize + x as usize]
+    }
+
+    fn bilinear(&self, u: f64, v: f64) -> Color {
+        let s = u * f64::from(self.width) - 0.5;
+        let t = (1.0 - v) * f64::from(self.height) - 0.5;
+
+        let x = s.floor();
+        let y = t.floor();
+        let ds = s - x;
+        let dt = t - y;
+        let (x, y) = (x as i64, y as i64);
+
+        self.texel(x, y) * ((1.0 - ds) * (1.0 - dt))
+            + self.texel(x + 1, y) * (ds * (1.0 - dt))
+            + self.texel(x, y + 1) * ((1.0 - ds) * dt)
+            + self.texel(x + 1, y + 1) * (ds * dt)
+    }
+}
+
+impl std::fmt::Debug for MipMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (width, height) = self.dimensions();
+        write!(
+            f,
+            "MipMap {{ width: {}, height: {}, levels: {}, hash: {:016x} }}",
+            width,
+            height,
+            self.levels.len(),
+            self.hash
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use image::{Rgb, RgbImage};
+
+    /// Checkerboard of single texel black and white squares.
+    fn checkerboard(width: u32, height: u32) -> MipMap {
+        let image = RgbImage::from_fn(width, height, |x, y| {
+            if (x + y) % 2 == 0 {
+                Rgb([255, 255, 255])
+            } else {
+                Rgb([0, 0, 0])
+            }
+        });
+
+        MipMap::new(&DynamicImage::ImageRgb8(image))
+    }
+
+    #[test]
+    fn builds_levels_down_to_a_single_texel() {
+        let mipmap = checkerboard(16, 4);
+        let sizes: Vec<_> = mipmap
+            .levels
+            .iter()
+            .map(|level| (level.width, level.height))
+            .collect();
+
+        assert_eq!(sizes, [(16, 4), (8, 2), (4, 1), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn filters_to_the_mean_over_large_areas() {
+        for (width, height) in [(16, 16), (16, 4), (4, 16)] {
+            let mipmap = checkerboard(width, height);
+
+            for (u, v) in [(0.1, 0.2), (0.5, 0.5), (0.8, 0.3)] {
+                let color = mipmap.lookup(u, v, 1.0);
+
+                assert!((color.r - 0.5).abs() < 1e-9, "{}x{}", width, height);
+            }
+        }
+    }
+
+    #[test]
+    fn stays_sharp_over_small_areas() {
+        for (width, height) in [(16, 16), (16, 4), (4, 16)] {
+            let mipmap = checkerboard(width, height);
+
+            // Texel centers, within a fraction of a texel.
+            let u = 2.5 / f64::from(width);
+            let v = 1.0 - 1.5 / f64::from(height);
+            let color = mipmap.lookup(u, v, 0.1 / f64::from(width.max(height)));
+
+            assert_eq!(color.r, 0.0, "{}x{}", width, height);
+
+            let u = 1.5 / f64::from(width);
+            let color = mipmap.lookup(u, v, 0.1 / f64::from(width.max(height)));
+
+            assert_eq!(color.r, 1.0, "{}x{}", width, height);
+        }
+    }
+
+    #[test]
+    fn blurs_one_level_per_doubling_of_the_area() {
+        let mipmap = checkerboard(16, 4);
+
+        // Two texels across the longest side averages neighbours, which on
+        // a checkerboard is already the mean.
+        let color = mipmap.lookup(0.5, 0.5, 2.0 / 16.0);
+
+        assert!((color.r - 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,29 @@
+use super::{Texture, TextureObject};
+use crate::Color;
+use crate::Vec3;
+
+/// Blends two textures, `factor` going from all `a` at 0.0 to all `b` at 1.0.
+#[derive(Clone, Debug)]
+pub struct Mix {
+    pub a: Box<Texture>,
+    pub b: Box<Texture>,
+    pub factor: Box<Texture>,
+}
+
+impl Mix {
+    fn combine(&self, lookup: impl Fn(&Texture) -> Color) -> Color {
+        let factor = super::scalar(lookup(&self.factor));
+
+        lookup(&self.a) * (1.0 - factor) + lookup(&self.b) * factor
+    }
+}
+
+impl TextureObject for Mix {
+    fn value(&self, u: f64, v: f64, p: Vec3) -> Color {
+        self.combine(|texture| texture.value(u, v, p))
+    }
+
+    fn filtered_value(&self, u: f64, v: f64, p: Vec3, width: f64) -> Color {
+        self.combine(|texture| texture.filtered_value(u, v, p, width))
+    }
+}
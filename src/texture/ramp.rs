@@ -0,0 +1,52 @@
+use super::{Texture, TextureObject};
+use crate::Color;
+use crate::Vec3;
+
+/// Maps a texture to colors, interpolating linearly between the colors of
+/// the stops around its value.
+///
+/// # Members
+/// * `input` - Texture whose value, the average of its channels, picks the
+///   color.
+/// * `stops` - Positions and their colors, sorted by position. Values before
+///   the first stop or after the last one take their color.
+#[derive(Clone, Debug)]
+pub struct Ramp {
+    pub input: Box<Texture>,
+    pub stops: Vec<(f64, Color)>,
+}
+
+impl Ramp {
+    fn map(&self, color: Color) -> Color {
+        let x = super::scalar(color);
+
+        let next = self.stops.partition_point(|&(position, _)| position <= x);
+
+        if next == 0 {
+            return self
+                .stops
+                .first()
+                .map_or(Color::default(), |&(_, color)| color);
+        }
+
+        if next == self.stops.len() {
+            return self.stops[next - 1].1;
+        }
+
+        let (start, start_color) = self.stops[next - 1];
+        let (end, end_color) = self.stops[next];
+        let t = (x - start) / (end - start);
+
+        start_color * (1.0 - t) + end_color * t
+    }
+}
+
+impl TextureObject for Ramp {
+    fn value(&self, u: f64, v: f64, p: Vec3) -> Color {
+        self.map(self.input.value(u, v, p))
+    }
+
+    fn filtered_value(&self, u: f64, v: f64, p: Vec3, width: f64) -> Color {
+        self.map(self.input.filtered_value(u, v, p, width))
+    }
+}
@@ -0,0 +1,20 @@
+use super::{Texture, TextureObject};
+use crate::Color;
+use crate::Vec3;
+
+/// Adds two textures channel by channel.
+#[derive(Clone, Debug)]
+pub struct Sum {
+    pub a: Box<Texture>,
+    pub b: Box<Texture>,
+}
+
+impl TextureObject for Sum {
+    fn value(&self, u: f64, v: f64, p: Vec3) -> Color {
+        self.a.value(u, v, p) + self.b.value(u, v, p)
+    }
+
+    fn filtered_value(&self, u: f64, v: f64, p: Vec3, width: f64) -> Color {
+        self.a.filtered_value(u, v, p, width) + self.b.filtered_value(u, v, p, width)
+    }
+}
@@ -0,0 +1,138 @@
+//! Splitting of the image into tiles that are rendered as a unit of work.
+
+/// Rectangular region of the image, in pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Order in which the tiles of the image are rendered.
+///
+/// * `Scanline` - Rows of tiles from top to bottom, left to right.
+/// * `Spiral` - From the center of the image outwards, so the usually most
+///   interesting part of the image finishes first.
+/// * `Hilbert` - Along a Hilbert curve, consecutive tiles are always neighbors
+///   which helps with cache locality.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileOrder {
+    Scanline,
+    Spiral,
+    Hilbert,
+}
+
+impl Tile {
+    pub fn area(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// The tile grown by `border` pixels on every side, clipped to an image of
+    /// `width` x `height` pixels.
+    pub fn grow(&self, border: u32, width: u32, height: u32) -> Tile {
+        let x = self.x.saturating_sub(border);
+        let y = self.y.saturating_sub(border);
+
+        Tile {
+            x,
+            y,
+            width: (self.x + self.width + border).min(width) - x,
+            height: (self.y + self.height + border).min(height) - y,
+        }
+    }
+
+    /// Image coordinates of the pixels of the tile, row by row.
+    pub fn pixels(&self) -> impl Iterator<Item = (u32, u32)> {
+        let tile = *self;
+
+        (tile.y..tile.y + tile.height)
+            .flat_map(move |y| (tile.x..tile.x + tile.width).map(move |x| (x, y)))
+    }
+}
+
+/// Splits `region` into tiles of `size` x `size` pixels (smaller on the right
+/// and bottom edges) sorted in the given `order`.
+pub fn tiles(region: Tile, size: u32, order: TileOrder) -> Vec<Tile> {
+    let Tile { width, height, .. } = region;
+    let columns = width.div_ceil(size);
+    let rows = height.div_ceil(size);
+
+    let mut grid: Vec<(u32, u32)> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (column, row)))
+        .collect();
+
+    match order {
+        TileOrder::Scanline => {}
+        TileOrder::Spiral => {
+            let cx = f64::from(columns - 1) / 2.0;
+            let cy = f64::from(rows - 1) / 2.0;
+
+            // Sort by ring around the center, then by angle within the ring.
+            let key = |&(column, row): &(u32, u32)| {
+                let dx = f64::from(column) - cx;
+                let dy = f64::from(row) - cy;
+
+                (dx.abs().max(dy.abs()), dy.atan2(dx))
+            };
+
+            grid.sort_by(|a, b| {
+                let (a_ring, a_angle) = key(a);
+                let (b_ring, b_angle) = key(b);
+
+                a_ring.total_cmp(&b_ring).then(a_angle.total_cmp(&b_angle))
+            });
+        }
+        TileOrder::Hilbert => {
+            let n = columns.max(rows).next_power_of_two();
+
+            grid.sort_by_key(|&(column, row)| hilbert_index(n, column, row));
+        }
+    }
+
+    grid.into_iter()
+        .map(|(column, row)| {
+            let x = column * size;
+            let y = row * size;
+
+            Tile {
+                x: region.x + x,
+                y: region.y + y,
+                width: size.min(width - x),
+                height: size.min(height - y),
+            }
+        })
+        .collect()
+}
+
+/// Distance along the Hilbert curve filling a `n` x `n` grid (`n` a power of
+/// two) of the cell at `x`, `y`.
+fn hilbert_index(n: u32, mut x: u32, mut y: u32) -> u64 {
+    let mut d = 0;
+    let mut s = n / 2;
+
+    while s > 0 {
+        let rx = u32::from(x & s > 0);
+        let ry = u32::from(y & s > 0);
+
+        d += u64::from(s) * u64::from(s) * u64::from((3 * rx) ^ ry);
+
+        // Rotate the quadrant so the curve is continuous.
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - (x & (s - 1));
+                y = s - 1 - (y & (s - 1));
+            }
+
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        s /= 2;
+    }
+
+    d
+}
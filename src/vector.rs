@@ -43,6 +43,10 @@ impl Vec3 {
     pub fn normalize(&self) -> Vec3 {
         *self / self.length()
     }
+
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
 }
 
 impl Add for Vec3 {
@@ -0,0 +1,83 @@
+mod common;
+
+use pathtracer::shape::Sphere;
+use pathtracer::{Aov, Color, Intersectable, Material, Ray, RenderSettings, Texture, Vec3, BVH};
+
+use std::path::Path;
+
+fn lambertian(color: Color) -> Material {
+    Material::lambertian(Texture::constant_color(color))
+}
+
+/// Material id of the object `ray` hits first.
+fn material_id(bvh: &BVH, ray: &Ray) -> u64 {
+    bvh.intersect(ray, 0.0001, f64::INFINITY)
+        .unwrap()
+        .material_id
+}
+
+#[test]
+fn equal_materials_share_their_id() {
+    let red = Color::new(0.75, 0.25, 0.25);
+    let blue = Color::new(0.25, 0.25, 0.75);
+
+    let bvh = BVH::from_vec(
+        [(-2.0, red), (0.0, red), (2.0, blue)]
+            .iter()
+            .map(|&(x, color)| -> pathtracer::Hitable {
+                Box::new(Sphere {
+                    center: Vec3::new(x, 0.0, 0.0),
+                    radius: 0.5,
+                    material: lambertian(color),
+                })
+            })
+            .collect(),
+    )
+    .unwrap();
+
+    let ray_at = |x: f64| Ray::new(Vec3::new(x, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+
+    assert_eq!(
+        material_id(&bvh, &ray_at(-2.0)),
+        material_id(&bvh, &ray_at(0.0))
+    );
+    assert_ne!(
+        material_id(&bvh, &ray_at(0.0)),
+        material_id(&bvh, &ray_at(2.0))
+    );
+}
+
+#[test]
+fn material_id_layer_survives_a_checkpoint() {
+    let directory = common::directory("material_id_checkpoint");
+    let partial = directory.join("half.partial");
+    let resumed = directory.join("resumed.png");
+    let uninterrupted = directory.join("uninterrupted.png");
+
+    let settings = RenderSettings {
+        aovs: vec![Aov::MaterialId],
+        ..common::settings()
+    };
+    let half = RenderSettings {
+        samples: 2,
+        ..settings.clone()
+    };
+
+    pathtracer::render_partial(common::scene(), &half, &partial).unwrap();
+    pathtracer::resume(
+        common::scene(),
+        &settings,
+        &partial,
+        resumed.to_str().unwrap(),
+    )
+    .unwrap();
+    pathtracer::render(common::scene(), &settings, uninterrupted.to_str().unwrap()).unwrap();
+
+    let layer = |image: &Path| {
+        image::open(image.with_extension("material_id.exr"))
+            .unwrap()
+            .to_rgb32f()
+    };
+
+    assert_eq!(layer(&resumed), layer(&uninterrupted));
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use pathtracer::shape::Sphere;
+use pathtracer::{
+    Checkpoint, Error, Integrator, Material, RenderSettings, Scene, Texture, Vec3, BVH,
+};
+
+use std::path::Path;
+use std::time::Duration;
+
+/// `common::scene` with a sphere textured with a 2x2 image of `color`.
+fn textured_scene(directory: &Path, name: &str, color: [u8; 3]) -> Scene {
+    let path = directory.join(name);
+
+    image::RgbImage::from_pixel(2, 2, image::Rgb(color))
+        .save(&path)
+        .unwrap();
+
+    let mut objects = common::objects();
+
+    objects.push(Box::new(Sphere {
+        center: Vec3::new(0.0, 0.5, 2.0),
+        radius: 0.5,
+        material: Material::lambertian(Texture::bitmap(&path).unwrap()),
+    }));
+
+    Scene {
+        camera: common::scene().camera,
+        world: BVH::from_vec(objects).unwrap(),
+    }
+}
+
+#[test]
+fn resumed_render_matches_uninterrupted_render() {
+    let directory = common::directory("resumed_matches");
+    let partial = directory.join("half.partial");
+    let resumed = directory.join("resumed.png");
+    let uninterrupted = directory.join("uninterrupted.png");
+
+    let half = RenderSettings {
+        samples: 2,
+        ..common::settings()
+    };
+
+    pathtracer::render_partial(common::scene(), &half, &partial).unwrap();
+    pathtracer::resume(
+        common::scene(),
+        &common::settings(),
+        &partial,
+        resumed.to_str().unwrap(),
+    )
+    .unwrap();
+    pathtracer::render(
+        common::scene(),
+        &common::settings(),
+        uninterrupted.to_str().unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        image::open(&resumed).unwrap(),
+        image::open(&uninterrupted).unwrap()
+    );
+}
+
+#[test]
+fn refuses_checkpoints_of_other_settings() {
+    let directory = common::directory("refuses_other_settings");
+    let partial = directory.join("checkpoint.partial");
+    let image = directory.join("image.png");
+
+    pathtracer::render_partial(common::scene(), &common::settings(), &partial).unwrap();
+
+    let changes = [
+        RenderSettings {
+            integrator: Integrator::Spectral,
+            ..common::settings()
+        },
+        RenderSettings {
+            seed: 1,
+            ..common::settings()
+        },
+        RenderSettings {
+            max_depth: 4,
+            ..common::settings()
+        },
+    ];
+
+    for settings in &changes {
+        let result =
+            pathtracer::resume(common::scene(), settings, &partial, image.to_str().unwrap());
+
+        assert!(matches!(result, Err(Error::InvalidCheckpoint(_))));
+    }
+
+    assert!(!image.exists());
+}
+
+#[test]
+fn refuses_truncated_checkpoints() {
+    let directory = common::directory("refuses_truncated");
+    let partial = directory.join("checkpoint.partial");
+    let image = directory.join("image.png");
+
+    pathtracer::render_partial(common::scene(), &common::settings(), &partial).unwrap();
+
+    let bytes = std::fs::read(&partial).unwrap();
+    std::fs::write(&partial, &bytes[..bytes.len() / 2]).unwrap();
+
+    let result = pathtracer::resume(
+        common::scene(),
+        &common::settings(),
+        &partial,
+        image.to_str().unwrap(),
+    );
+
+    assert!(matches!(result, Err(Error::InvalidCheckpoint(_))));
+}
+
+#[test]
+fn refuses_checkpoints_of_other_textures() {
+    let directory = common::directory("refuses_other_textures");
+    let partial = directory.join("checkpoint.partial");
+    let image = directory.join("image.png");
+
+    let scene = textured_scene(&directory, "red.png", [255, 0, 0]);
+
+    pathtracer::render_partial(scene, &common::settings(), &partial).unwrap();
+
+    let scene = textured_scene(&directory, "blue.png", [0, 0, 255]);
+    let result = pathtracer::resume(
+        scene,
+        &common::settings(),
+        &partial,
+        image.to_str().unwrap(),
+    );
+
+    assert!(matches!(result, Err(Error::InvalidCheckpoint(_))));
+}
+
+#[test]
+fn checkpointed_render_matches_plain_render() {
+    let directory = common::directory("checkpointed_matches");
+    let checkpointed = directory.join("checkpointed.png");
+    let plain = directory.join("plain.png");
+
+    let settings = RenderSettings {
+        samples: 16,
+        checkpoint: Some(Checkpoint {
+            path: directory.join("checkpoint.partial"),
+            interval: Duration::from_secs(1),
+        }),
+        ..common::settings()
+    };
+
+    pathtracer::render(common::scene(), &settings, checkpointed.to_str().unwrap()).unwrap();
+
+    let settings = RenderSettings {
+        checkpoint: None,
+        ..settings
+    };
+
+    pathtracer::render(common::scene(), &settings, plain.to_str().unwrap()).unwrap();
+
+    assert!(directory.join("checkpoint.partial").exists());
+    assert_eq!(
+        image::open(&checkpointed).unwrap(),
+        image::open(&plain).unwrap()
+    );
+}
@@ -0,0 +1,74 @@
+//! Helpers shared by the integration tests.
+
+use pathtracer::shape::*;
+use pathtracer::{Camera, Color, Hitable, Material, RenderSettings, Scene, Texture, Vec3, BVH};
+
+use std::path::PathBuf;
+
+/// Light, floor and a diffuse and a glass sphere of `scene`.
+pub fn objects() -> Vec<Hitable> {
+    let white = Color::new(0.75, 0.75, 0.75);
+    let light = Color::new(1.0, 1.0, 1.0) * 10.0;
+
+    vec![
+        Box::new(Disc {
+            center: Vec3::new(0.0, 6.0, 0.0),
+            radius: 2.0,
+            normal: Vec3::new(0.0, -1.0, 0.0),
+            material: Material::diffuse_light(Texture::constant_color(light)),
+        }),
+        Box::new(Sphere {
+            center: Vec3::new(0.0, -1000.0, 0.0),
+            radius: 1000.0,
+            material: Material::lambertian(Texture::constant_color(white)),
+        }),
+        Box::new(Sphere {
+            center: Vec3::new(-1.2, 1.0, 0.0),
+            radius: 1.0,
+            material: Material::lambertian(Texture::constant_color(Color::new(0.75, 0.25, 0.25))),
+        }),
+        Box::new(Sphere {
+            center: Vec3::new(1.2, 1.0, 0.0),
+            radius: 1.0,
+            material: Material::dielectric(Texture::constant_color(white), 1.5),
+        }),
+    ]
+}
+
+/// Small lit scene with a diffuse and a glass sphere on a floor.
+pub fn scene() -> Scene {
+    Scene {
+        camera: Camera::new(
+            Vec3::new(0.0, 2.0, 8.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            45.0,
+            4.0 / 3.0,
+            0.0,
+        )
+        .unwrap(),
+        world: BVH::from_vec(objects()).unwrap(),
+    }
+}
+
+/// Settings for a quick render of `scene`.
+pub fn settings() -> RenderSettings {
+    RenderSettings {
+        width: 32,
+        height: 24,
+        samples: 4,
+        max_depth: 8,
+        workers: 1,
+        tile_size: 8,
+        ..RenderSettings::default()
+    }
+}
+
+/// Empty directory for the files written by the test `name`.
+pub fn directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join("pathtracer-tests").join(name);
+
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    directory
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use pathtracer::{RenderSettings, SamplerKind};
+
+#[test]
+fn renders_the_same_image_with_any_number_of_workers() {
+    let directory = common::directory("any_number_of_workers");
+
+    for sampler in [SamplerKind::Independent, SamplerKind::Sobol] {
+        let images: Vec<_> = [1, 8]
+            .iter()
+            .map(|&workers| {
+                let settings = RenderSettings {
+                    workers,
+                    sampler,
+                    ..common::settings()
+                };
+                let image = directory.join(format!("{:?}-{}.png", sampler, workers));
+
+                pathtracer::render(common::scene(), &settings, image.to_str().unwrap()).unwrap();
+
+                std::fs::read(&image).unwrap()
+            })
+            .collect();
+
+        assert_eq!(images[0], images[1], "{:?} images differ", sampler);
+    }
+}
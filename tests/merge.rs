@@ -0,0 +1,143 @@
+mod common;
+
+use pathtracer::{Error, Filter, RenderSettings, Tile};
+
+use std::path::PathBuf;
+
+/// Renders the regions of the image, each one to a partial render.
+fn render_regions(name: &str, regions: &[Tile]) -> Vec<PathBuf> {
+    let directory = common::directory(name);
+
+    regions
+        .iter()
+        .enumerate()
+        .map(|(i, region)| {
+            let settings = RenderSettings {
+                region: Some(*region),
+                ..common::settings()
+            };
+            let partial = directory.join(format!("{}.partial", i));
+
+            pathtracer::render_partial(common::scene(), &settings, &partial).unwrap();
+
+            partial
+        })
+        .collect()
+}
+
+#[test]
+fn merges_regions_covering_the_image() {
+    let partials = render_regions(
+        "merges_regions",
+        &[
+            Tile {
+                x: 0,
+                y: 0,
+                width: 32,
+                height: 10,
+            },
+            Tile {
+                x: 0,
+                y: 10,
+                width: 32,
+                height: 14,
+            },
+        ],
+    );
+    let image = partials[0].with_file_name("image.png");
+
+    pathtracer::merge(&partials, 2.2, image.to_str().unwrap()).unwrap();
+
+    assert_eq!(image::image_dimensions(&image).unwrap(), (32, 24));
+}
+
+#[test]
+fn refuses_regions_not_covering_the_image() {
+    let partials = render_regions(
+        "refuses_uncovered",
+        &[Tile {
+            x: 0,
+            y: 0,
+            width: 32,
+            height: 10,
+        }],
+    );
+    let image = partials[0].with_file_name("image.png");
+
+    let result = pathtracer::merge(&partials, 2.2, image.to_str().unwrap());
+
+    assert!(matches!(result, Err(Error::MismatchedPartials(_))));
+    assert!(!image.exists());
+}
+
+#[test]
+fn refuses_partials_with_different_settings() {
+    let directory = common::directory("refuses_different_settings");
+    let partials = [directory.join("a.partial"), directory.join("b.partial")];
+
+    pathtracer::render_partial(common::scene(), &common::settings(), &partials[0]).unwrap();
+
+    let settings = RenderSettings {
+        filter: Filter::Box { radius: 0.5 },
+        ..common::settings()
+    };
+
+    pathtracer::render_partial(common::scene(), &settings, &partials[1]).unwrap();
+
+    let result = pathtracer::merge(
+        &partials,
+        2.2,
+        directory.join("image.png").to_str().unwrap(),
+    );
+
+    assert!(matches!(result, Err(Error::MismatchedPartials(_))));
+}
+
+#[test]
+fn refuses_no_partials() {
+    let partials: [PathBuf; 0] = [];
+
+    let result = pathtracer::merge(&partials, 2.2, "image.png");
+
+    assert!(matches!(result, Err(Error::MismatchedPartials(_))));
+}
+
+#[test]
+fn refuses_overlapping_partials_with_the_same_seed() {
+    let directory = common::directory("refuses_same_seed");
+    let partials = [directory.join("a.partial"), directory.join("b.partial")];
+
+    for partial in &partials {
+        pathtracer::render_partial(common::scene(), &common::settings(), partial).unwrap();
+    }
+
+    let result = pathtracer::merge(
+        &partials,
+        2.2,
+        directory.join("image.png").to_str().unwrap(),
+    );
+
+    assert!(matches!(result, Err(Error::MismatchedPartials(_))));
+}
+
+#[test]
+fn merges_overlapping_partials_with_different_seeds() {
+    let directory = common::directory("merges_different_seeds");
+    let partials = [directory.join("a.partial"), directory.join("b.partial")];
+
+    for (seed, partial) in partials.iter().enumerate() {
+        let settings = RenderSettings {
+            seed: seed as u64,
+            ..common::settings()
+        };
+
+        pathtracer::render_partial(common::scene(), &settings, partial).unwrap();
+    }
+
+    pathtracer::merge(
+        &partials,
+        2.2,
+        directory.join("image.png").to_str().unwrap(),
+    )
+    .unwrap();
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use pathtracer::{Error, Filter, RenderSettings, Tile};
+
+#[test]
+fn refuses_regions_past_the_edge_of_the_image() {
+    let settings = RenderSettings {
+        region: Some(Tile {
+            x: u32::MAX,
+            y: 0,
+            width: 2,
+            height: 1,
+        }),
+        ..RenderSettings::default()
+    };
+
+    assert!(matches!(
+        settings.validate(),
+        Err(Error::InvalidSettings(_))
+    ));
+}
+
+#[test]
+fn refuses_filters_smaller_than_half_a_pixel() {
+    let settings = RenderSettings {
+        filter: Filter::Box { radius: 0.25 },
+        ..RenderSettings::default()
+    };
+
+    assert!(matches!(
+        settings.validate(),
+        Err(Error::InvalidSettings(_))
+    ));
+}
+
+#[test]
+fn refuses_images_too_large_to_address() {
+    let settings = RenderSettings {
+        width: u32::MAX,
+        height: u32::MAX,
+        ..common::settings()
+    };
+
+    let image = common::directory("too_large").join("image.png");
+
+    let result = pathtracer::render(common::scene(), &settings, image.to_str().unwrap());
+
+    assert!(matches!(result, Err(Error::InvalidSettings(_))));
+}
@@ -0,0 +1,87 @@
+use pathtracer::shape::{self, Disc, Sphere};
+use pathtracer::{Color, Intersectable, Intersection, Material, Ray, Texture, Vec3, AABB};
+
+fn material() -> Material {
+    Material::lambertian(Texture::constant_color(Color::new(0.5, 0.5, 0.5)))
+}
+
+/// Unit square on the XY plane, a shape built with `Intersection::new`.
+#[derive(Debug)]
+struct Square {
+    material: Material,
+}
+
+impl Intersectable for Square {
+    fn intersect(&self, ray: &Ray, min: f64, max: f64) -> Option<Intersection<'_>> {
+        let t = -ray.origin.z / ray.direction.z;
+
+        if !(t > min && t < max) {
+            return None;
+        }
+
+        let p = ray.point_at(t);
+
+        if !(0.0..=1.0).contains(&p.x) || !(0.0..=1.0).contains(&p.y) {
+            return None;
+        }
+
+        Some(Intersection::new(
+            t,
+            p,
+            Vec3::new(0.0, 0.0, 1.0),
+            p.x,
+            p.y,
+            &self.material,
+        ))
+    }
+
+    fn bounding_box(&self) -> AABB {
+        AABB {
+            min: Vec3::new(0.0, 0.0, 0.0),
+            max: Vec3::new(1.0, 1.0, 0.0),
+        }
+    }
+}
+
+#[test]
+fn sphere_keeps_the_shape_invariants() {
+    let sphere = Sphere {
+        center: Vec3::new(1.0, -2.0, 3.0),
+        radius: 1.5,
+        material: material(),
+    };
+
+    shape::validate(&sphere, 10_000, 0).unwrap();
+}
+
+#[test]
+fn hollow_sphere_keeps_the_shape_invariants() {
+    let sphere = Sphere {
+        center: Vec3::new(0.0, 0.0, 0.0),
+        radius: -0.5,
+        material: material(),
+    };
+
+    shape::validate(&sphere, 10_000, 0).unwrap();
+}
+
+#[test]
+fn disc_keeps_the_shape_invariants() {
+    let disc = Disc {
+        center: Vec3::new(0.0, 2.0, -1.0),
+        normal: Vec3::new(1.0, 1.0, 0.0).normalize(),
+        radius: 2.0,
+        material: material(),
+    };
+
+    shape::validate(&disc, 10_000, 0).unwrap();
+}
+
+#[test]
+fn custom_shape_keeps_the_shape_invariants() {
+    let square = Square {
+        material: material(),
+    };
+
+    shape::validate(&square, 10_000, 0).unwrap();
+}